@@ -0,0 +1,102 @@
+use bevy::{
+    asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    sprite::Rect,
+    utils::HashMap,
+};
+
+// a kenney spritesheet: the atlas built from its xml plus a lookup of each SubTexture by name
+#[derive(Clone, Debug, TypeUuid)]
+#[uuid = "6c7a1a5e-2f4b-4d8e-9a0c-3b1f5e7d2c41"]
+pub struct SpriteSheet {
+    pub atlas: Handle<TextureAtlas>,
+    names: HashMap<String, u32>,
+}
+
+impl SpriteSheet {
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.names.get(name).copied()
+    }
+
+    // the first sprite stands in for a missing one, levels are checked against their sheets when loaded
+    pub fn index(&self, name: &str) -> u32 {
+        match self.get(name) {
+            Some(index) => index,
+            None => {
+                warn!("sprite {} is not in the spritesheet", name);
+                0
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SpriteSheetLoader;
+
+impl AssetLoader for SpriteSheetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let xml = std::str::from_utf8(bytes)?;
+            let document = roxmltree::Document::parse(xml)?;
+
+            // the imagePath attribute in the kenney sheets is always "sprites.png",
+            // so the png is expected next to the xml with the same file name
+            let image_path = load_context.path().with_extension("png");
+            let image_bytes = load_context.read_asset_bytes(&image_path).await?;
+            let texture = load_context.get_handle(AssetPath::new_ref(&image_path, None));
+            let mut atlas = TextureAtlas::new_empty(texture, png_size(&image_bytes)?);
+
+            let mut names = HashMap::default();
+            for node in document
+                .descendants()
+                .filter(|node| node.has_tag_name("SubTexture"))
+            {
+                let name = node
+                    .attribute("name")
+                    .ok_or_else(|| anyhow::anyhow!("SubTexture is missing a name"))?;
+                let x = attribute(&node, "x")?;
+                let y = attribute(&node, "y")?;
+                let width = attribute(&node, "width")?;
+                let height = attribute(&node, "height")?;
+                names.insert(name.to_string(), atlas.len() as u32);
+                atlas.add_texture(Rect {
+                    min: Vec2::new(x, y),
+                    max: Vec2::new(x + width, y + height),
+                });
+            }
+
+            let atlas = load_context.set_labeled_asset("atlas", LoadedAsset::new(atlas));
+            load_context.set_default_asset(
+                LoadedAsset::new(SpriteSheet { atlas, names })
+                    .with_dependency(AssetPath::new(image_path, None)),
+            );
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xml"]
+    }
+}
+
+fn attribute(node: &roxmltree::Node, name: &str) -> Result<f32, anyhow::Error> {
+    let value = node
+        .attribute(name)
+        .ok_or_else(|| anyhow::anyhow!("SubTexture is missing {}", name))?;
+    Ok(value.parse()?)
+}
+
+// read the width and height from the png IHDR chunk
+fn png_size(bytes: &[u8]) -> Result<Vec2, anyhow::Error> {
+    if bytes.len() < 24 || &bytes[1..4] != b"PNG" || &bytes[12..16] != b"IHDR" {
+        anyhow::bail!("spritesheet image is not a png");
+    }
+    let width = u32::from_be_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+    let height = u32::from_be_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+    Ok(Vec2::new(width as f32, height as f32))
+}
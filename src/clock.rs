@@ -0,0 +1,111 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*, utils::Duration};
+
+// length of a simulation step, the game only ever moves on by whole steps
+pub const STEP: f64 = 1. / 60.;
+pub const STEPS_PER_SECOND: u64 = 60;
+
+// paces the simulation stage, fed with the frame time or moved on by hand when headless
+#[derive(Default)]
+pub struct SimulationClock {
+    manual: bool,
+    // time owed to the simulation that doesn't add up to a whole step yet
+    accumulator: f64,
+    // steps simulated since the app started
+    pub tick: u64,
+    // set by a step that changes the game state, the remaining steps wait for the transition
+    held: bool,
+}
+
+impl SimulationClock {
+    pub fn manual() -> Self {
+        SimulationClock {
+            manual: true,
+            ..Default::default()
+        }
+    }
+
+    // switch between stepping by hand and keeping up with the frame time
+    pub fn set_manual(&mut self, manual: bool) {
+        self.manual = manual;
+        self.accumulator = 0.;
+    }
+
+    // owe the simulation some steps, for a clock driven by hand
+    pub fn advance(&mut self, steps: u64) {
+        self.accumulator += steps as f64 * STEP;
+    }
+
+    pub fn hold(&mut self) {
+        self.held = true;
+    }
+
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f64(STEP)
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        STEP as f32
+    }
+
+    // how far into the next step the frame is, for drawing motion between steps
+    pub fn overstep(&self) -> f32 {
+        (self.accumulator / STEP).min(1.) as f32
+    }
+}
+
+// simulated position of a moving entity, its transform is interpolated between the last two ticks
+pub struct Motion {
+    pub previous: Vec3,
+    pub current: Vec3,
+}
+
+impl Motion {
+    pub fn new(position: Vec3) -> Self {
+        Motion {
+            previous: position,
+            current: position,
+        }
+    }
+}
+
+// run the simulation stage once for every step owed
+pub fn run_steps(
+    time: Res<Time>,
+    mut clock: ResMut<SimulationClock>,
+    mut looping: Local<bool>,
+) -> ShouldRun {
+    if !*looping {
+        if !clock.manual {
+            clock.accumulator += time.delta_seconds_f64();
+        }
+        clock.held = false;
+    }
+    // a little slack so steps owed by hand aren't lost to rounding
+    if !clock.held && clock.accumulator >= STEP - f64::EPSILON {
+        clock.accumulator = (clock.accumulator - STEP).max(0.);
+        clock.tick += 1;
+        *looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *looping = false;
+        ShouldRun::No
+    }
+}
+
+// place moving entities between their last two simulated positions so motion stays smooth at any frame rate
+pub fn interpolate_motion(
+    clock: Res<SimulationClock>,
+    mut query: Query<(&mut Transform, &Motion)>,
+) {
+    let overstep = clock.overstep();
+    for (mut transform, motion) in query.iter_mut() {
+        transform.translation = motion.previous.lerp(motion.current, overstep);
+    }
+}
+
+// stop interpolating once the steps stop, or frozen entities keep wobbling between their last two positions
+pub fn settle_motion(mut query: Query<&mut Motion>) {
+    for mut motion in query.iter_mut() {
+        motion.previous = motion.current;
+    }
+}
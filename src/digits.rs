@@ -0,0 +1,163 @@
+use crate::Textures;
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+// distance between the centres of neighbouring glyphs
+const GLYPH_ADVANCE: f32 = 28.;
+
+pub enum DigitFormat {
+    // the value as it is, e.g. a score or a number of seconds
+    Number,
+    // the value as minutes and seconds, the minutes growing as needed
+    Clock,
+    // the value after a plus sign, for points won
+    Plus,
+    // the value after a cross, for multipliers
+    Times,
+}
+
+// fill shorter values up to a number of glyphs
+pub enum Padding {
+    None,
+    Zeros(usize),
+    Blanks(usize),
+}
+
+// where the glyphs sit relative to the display's position
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// a number drawn with the hud's digit sprites, one child sprite per glyph
+pub struct DigitDisplay {
+    pub value: usize,
+    pub format: DigitFormat,
+    pub padding: Padding,
+    pub align: Align,
+    // tint of the glyphs
+    pub color: Color,
+}
+
+impl DigitDisplay {
+    pub fn new(format: DigitFormat, padding: Padding, align: Align) -> Self {
+        DigitDisplay {
+            value: 0,
+            format,
+            padding,
+            align,
+            color: Color::WHITE,
+        }
+    }
+
+    fn glyphs(&self) -> Vec<char> {
+        let text = match self.format {
+            DigitFormat::Number => self.value.to_string(),
+            DigitFormat::Clock => format!("{}:{:02}", self.value / 60, self.value % 60),
+            DigitFormat::Plus => format!("+{}", self.value),
+            DigitFormat::Times => format!("x{}", self.value),
+        };
+        let (fill, width) = match self.padding {
+            Padding::None => (' ', 0),
+            Padding::Zeros(width) => ('0', width),
+            Padding::Blanks(width) => (' ', width),
+        };
+        let mut glyphs = vec![fill; width.saturating_sub(text.len())];
+        glyphs.extend(text.chars());
+        glyphs
+    }
+}
+
+pub fn spawn_digits<'a, 'b>(
+    commands: &'b mut Commands<'a>,
+    position: Vec3,
+    display: DigitDisplay,
+) -> EntityCommands<'a, 'b> {
+    let mut entity = commands.spawn_bundle((
+        Transform::from_translation(position),
+        GlobalTransform::identity(),
+    ));
+    entity.insert(display);
+    entity
+}
+
+// lay out a display's glyph sprites again whenever its value changes
+pub fn render_digits(
+    mut commands: Commands,
+    texture: Res<Textures>,
+    displays: Query<(Entity, &DigitDisplay, Option<&Children>), Changed<DigitDisplay>>,
+) {
+    for (entity, display, children) in displays.iter() {
+        for child in children.iter().flat_map(|children| children.iter()) {
+            commands.entity(*child).despawn_recursive();
+        }
+
+        let glyphs = display.glyphs();
+        let width = (glyphs.len().max(1) - 1) as f32 * GLYPH_ADVANCE;
+        let start = match display.align {
+            Align::Left => 0.,
+            Align::Center => -width / 2.,
+            Align::Right => -width,
+        };
+        commands.entity(entity).with_children(|parent| {
+            for (slot, glyph) in glyphs.into_iter().enumerate() {
+                let index = match glyph {
+                    ':' => texture.sprites_hud.index("text_dots_small.png"),
+                    '+' => texture.sprites_hud.index("text_plus_small.png"),
+                    'x' => texture.sprites_hud.index("text_cross_small.png"),
+                    _ => match glyph.to_digit(10) {
+                        Some(digit) => texture.digit(digit),
+                        None => continue,
+                    },
+                };
+                parent.spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: texture.sprites_hud.atlas.clone(),
+                    transform: Transform::from_xyz(start + slot as f32 * GLYPH_ADVANCE, 0., 0.),
+                    sprite: TextureAtlasSprite {
+                        index,
+                        color: display.color,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyphs(format: DigitFormat, padding: Padding, value: usize) -> String {
+        let mut display = DigitDisplay::new(format, padding, Align::Left);
+        display.value = value;
+        display.glyphs().into_iter().collect()
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(glyphs(DigitFormat::Number, Padding::None, 1250), "1250");
+        assert_eq!(glyphs(DigitFormat::Clock, Padding::None, 65), "1:05");
+        assert_eq!(glyphs(DigitFormat::Clock, Padding::None, 600), "10:00");
+        assert_eq!(glyphs(DigitFormat::Plus, Padding::None, 50), "+50");
+        assert_eq!(glyphs(DigitFormat::Times, Padding::None, 3), "x3");
+    }
+
+    #[test]
+    fn zeros_fill_up_to_the_width() {
+        assert_eq!(glyphs(DigitFormat::Number, Padding::Zeros(4), 42), "0042");
+        assert_eq!(glyphs(DigitFormat::Number, Padding::Zeros(4), 0), "0000");
+        assert_eq!(
+            glyphs(DigitFormat::Number, Padding::Zeros(4), 12345),
+            "12345"
+        );
+    }
+
+    #[test]
+    fn blanks_fill_up_to_the_width() {
+        assert_eq!(glyphs(DigitFormat::Number, Padding::Blanks(4), 42), "  42");
+        assert_eq!(glyphs(DigitFormat::Plus, Padding::Blanks(4), 5), "  +5");
+        assert_eq!(glyphs(DigitFormat::Number, Padding::Blanks(2), 123), "123");
+    }
+}
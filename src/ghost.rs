@@ -0,0 +1,155 @@
+use crate::{
+    level::Level,
+    menu::Settings,
+    mode::GameMode,
+    replay::{Button, Playback, Replay, ReplayInput, RoundSteps},
+    waves::Difficulty,
+    AssetsReloaded, GameState, Textures, GHOST_LABEL, HOT_RELOAD_LABEL,
+};
+use bevy::prelude::*;
+
+const GHOST_COLOR: Color = Color::rgba(1., 1., 1., 0.45);
+
+pub struct GhostCrosshair;
+
+// the best run of the level played alongside the round, aiming and firing where it did
+#[derive(Default)]
+pub struct Ghost {
+    // whether there's a best run to follow this round
+    pub running: bool,
+    // the best run's score at the current step
+    pub score: usize,
+    frames: Vec<(u64, Vec<ReplayInput>)>,
+    scores: Vec<(u64, usize)>,
+    next_frame: usize,
+    next_score: usize,
+}
+
+impl Ghost {
+    fn follow(replay: &Replay) -> Self {
+        Ghost {
+            running: true,
+            score: 0,
+            frames: replay.frames(),
+            scores: replay.scores(),
+            next_frame: 0,
+            next_score: 0,
+        }
+    }
+}
+
+// a translucent crosshair replaying the best run of the level, mode and difficulty
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Ghost>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Countdown)
+                    .with_system(load_ghost.system().label(GHOST_LABEL)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Countdown).with_system(follow_ghost.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(follow_ghost.system())
+                    .with_system(respawn_ghost.system().after(HOT_RELOAD_LABEL)),
+            );
+    }
+}
+
+// pick up the best run for the round, unless it's turned off or a replay is being played
+fn load_ghost(
+    mut commands: Commands,
+    mut ghost: ResMut<Ghost>,
+    texture: Res<Textures>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    settings: Option<Res<Settings>>,
+    playback: Option<Res<Playback>>,
+) {
+    *ghost = Ghost::default();
+    if !settings.is_none_or(|settings| settings.ghost)
+        || playback.is_some_and(|playback| playback.active())
+    {
+        return;
+    }
+    let best = match Replay::best(&level.name, *mode, *difficulty) {
+        Some(best) => best,
+        None => return,
+    };
+    *ghost = Ghost::follow(&best);
+    spawn_ghost(&mut commands, &texture);
+}
+
+// put the ghost back after the gallery was rebuilt from changed assets
+fn respawn_ghost(
+    mut commands: Commands,
+    mut events: EventReader<AssetsReloaded>,
+    ghost: Res<Ghost>,
+    texture: Res<Textures>,
+) {
+    if events.iter().count() > 0 && ghost.running {
+        spawn_ghost(&mut commands, &texture);
+    }
+}
+
+fn spawn_ghost(commands: &mut Commands, texture: &Textures) {
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: texture.sprites_hud.atlas.clone(),
+            transform: Transform::from_xyz(0., 0., 3.9),
+            sprite: TextureAtlasSprite {
+                index: texture.sprites_hud.index("crosshair_blue_large.png"),
+                color: GHOST_COLOR,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(GhostCrosshair);
+}
+
+// play the best run's input and score up to the step the round is on
+fn follow_ghost(
+    mut ghost: ResMut<Ghost>,
+    steps: Res<RoundSteps>,
+    texture: Res<Textures>,
+    mut crosshairs: Query<(&mut Transform, &mut TextureAtlasSprite), With<GhostCrosshair>>,
+) {
+    if !ghost.running {
+        return;
+    }
+    let ghost = &mut *ghost;
+    while let Some((step, inputs)) = ghost.frames.get(ghost.next_frame) {
+        if *step > steps.0 {
+            break;
+        }
+        for (mut transform, mut sprite) in crosshairs.iter_mut() {
+            for input in inputs.iter() {
+                match input {
+                    ReplayInput::Cursor(x, y) => {
+                        transform.translation.x = *x;
+                        transform.translation.y = *y;
+                    }
+                    ReplayInput::Press(Button::Left) => {
+                        sprite.index = texture.sprites_hud.index("crosshair_white_small.png");
+                    }
+                    ReplayInput::Release(Button::Left) => {
+                        sprite.index = texture.sprites_hud.index("crosshair_blue_large.png");
+                    }
+                    _ => {}
+                }
+            }
+        }
+        ghost.next_frame += 1;
+    }
+    while let Some((step, score)) = ghost.scores.get(ghost.next_score).copied() {
+        if step > steps.0 {
+            break;
+        }
+        ghost.score = score;
+        ghost.next_score += 1;
+    }
+}
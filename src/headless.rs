@@ -0,0 +1,491 @@
+use crate::{
+    asset_folder,
+    clock::{self, Motion, SimulationClock},
+    ghost::GhostPlugin,
+    hit::AlphaMasks,
+    hud::HudPlugin,
+    level::Level,
+    level::TargetKind,
+    menu::MenuPlugin,
+    mode::GameMode,
+    replay::{Playback, Recording, Replay},
+    results::ResultsPlugin,
+    rng::GameRng,
+    stall::Cover,
+    targets::{KnockedDown, Target},
+    waves::Difficulty,
+    AssetHandles, GalleryPlugins, Game, GameState, Stats, Textures,
+};
+use bevy::{
+    app::Events,
+    asset::{AssetPlugin, AssetServerSettings, HandleId, LoadState},
+    ecs::component::Component,
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ElementState, InputPlugin},
+    prelude::*,
+    render::texture::ImageTextureLoader,
+    transform::TransformPlugin,
+    window::{WindowId, WindowPlugin},
+};
+use std::{thread, time::Duration};
+
+// updates to wait for the spritesheets and level to load in the background, a failed load ends the wait
+const LOAD_ATTEMPTS: usize = 1000;
+const LOAD_WAIT: Duration = Duration::from_millis(10);
+// steps between two shots of the bot
+const SHOT_INTERVAL: u64 = 30;
+// how close another target in front can be before a target counts as hidden behind it
+const CLEARANCE: f32 = 80.;
+// rounds without a clock are finished after this many steps
+const MAX_STEPS: u64 = 180 * clock::STEPS_PER_SECOND;
+
+// the gallery without a window or renderer, stepped by hand and fed made up input
+pub struct Simulation {
+    app: App,
+}
+
+impl Simulation {
+    pub fn new(mode: GameMode, seed: Option<u64>) -> anyhow::Result<Self> {
+        let mut builder = App::build();
+        builder
+            .insert_resource(AssetServerSettings {
+                asset_folder: asset_folder(),
+            })
+            .insert_resource(mode)
+            .insert_resource(seed.map_or_else(GameRng::default, GameRng::with_seed))
+            .insert_resource(SimulationClock::manual())
+            .add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(WindowPlugin {
+                add_primary_window: false,
+                exit_on_close: false,
+            })
+            .add_plugin(AssetPlugin)
+            // registered by the renderer otherwise, hit testing reads the alpha of the spritesheets
+            .add_asset::<Texture>()
+            .add_asset::<TextureAtlas>()
+            .init_asset_loader::<ImageTextureLoader>()
+            // the screens drawn over the gallery need the renderer and ui,
+            // and the ghost would race the player's own best run
+            .add_plugins_with(GalleryPlugins, |group| {
+                group
+                    .disable::<GhostPlugin>()
+                    .disable::<HudPlugin>()
+                    .disable::<MenuPlugin>()
+                    .disable::<ResultsPlugin>()
+            });
+        let mut app = builder.app;
+
+        // cursor positions are given in the coordinates of a window the size of the game's
+        let descriptor = WindowDescriptor::default();
+        app.world
+            .get_resource_mut::<Windows>()
+            .unwrap()
+            .add(Window::new(
+                WindowId::primary(),
+                &descriptor,
+                descriptor.width as u32,
+                descriptor.height as u32,
+                1.,
+                None,
+            ));
+
+        let mut simulation = Simulation { app };
+        for _ in 0..LOAD_ATTEMPTS {
+            simulation.app.update();
+            if simulation.loaded() {
+                return Ok(simulation);
+            }
+            if simulation.failed() {
+                anyhow::bail!(
+                    "the gallery's assets failed to load from {}",
+                    asset_folder()
+                );
+            }
+            thread::sleep(LOAD_WAIT);
+        }
+        anyhow::bail!("the gallery's assets didn't load in time")
+    }
+
+    // whether a spritesheet or the level couldn't be read or parsed
+    fn failed(&self) -> bool {
+        let world = &self.app.world;
+        let (server, handles) = match (
+            world.get_resource::<AssetServer>(),
+            world.get_resource::<AssetHandles>(),
+        ) {
+            (Some(server), Some(handles)) => (server, handles),
+            _ => return false,
+        };
+        let ids: [HandleId; 4] = [
+            handles.stall.id,
+            handles.hud.id,
+            handles.objects.id,
+            handles.level.id,
+        ];
+        ids.iter()
+            .any(|id| server.get_load_state(*id) == LoadState::Failed)
+    }
+
+    // whether the main menu is up and the spritesheet images for hit testing are in
+    fn loaded(&self) -> bool {
+        let world = &self.app.world;
+        if *world.get_resource::<State<GameState>>().unwrap().current() != GameState::MainMenu {
+            return false;
+        }
+        let (texture, atlases, images) = match (
+            world.get_resource::<Textures>(),
+            world.get_resource::<Assets<TextureAtlas>>(),
+            world.get_resource::<Assets<Texture>>(),
+        ) {
+            (Some(texture), Some(atlases), Some(images)) => (texture, atlases, images),
+            _ => return false,
+        };
+        [&texture.sprites_stall, &texture.sprites_objects]
+            .iter()
+            .all(|sheet| {
+                atlases
+                    .get(&sheet.atlas)
+                    .is_some_and(|atlas| images.get(&atlas.texture).is_some())
+            })
+    }
+
+    // play a round from the main menu with a bot shooting at the best standing target every half
+    // second and reloading when empty, finishing rounds without a clock after a while
+    pub fn play_round(&mut self) {
+        self.start_round();
+        for step in 0..MAX_STEPS {
+            if self.state() != GameState::Playing {
+                break;
+            }
+            if step % SHOT_INTERVAL == 0 {
+                if self.game().ammo == 0 {
+                    self.press_key(KeyCode::R);
+                } else if let Some(target) = self.targets().first().copied() {
+                    self.move_cursor(target);
+                    self.click();
+                }
+            }
+            self.step();
+        }
+        if self.state() == GameState::Playing {
+            self.finish_round();
+        }
+        self.step_until(GameState::GameOver);
+    }
+
+    // step until the round gets to a state, giving up after a while
+    pub fn step_until(&mut self, state: GameState) {
+        for _ in 0..MAX_STEPS {
+            if self.state() == state {
+                break;
+            }
+            self.step();
+        }
+    }
+
+    // start a round from the main menu and play through the intro
+    pub fn start_round(&mut self) {
+        self.set_state(GameState::Countdown);
+        while self.state() != GameState::Playing {
+            self.step();
+        }
+    }
+
+    // end a round the way the pause menu does for rounds without a clock
+    pub fn finish_round(&mut self) {
+        self.set_state(GameState::TimeUp);
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        self.app
+            .world
+            .get_resource_mut::<State<GameState>>()
+            .unwrap()
+            .set(state)
+            .unwrap();
+        self.app.update();
+    }
+
+    // one frame of a single simulation step
+    pub fn step(&mut self) {
+        self.app
+            .world
+            .get_resource_mut::<SimulationClock>()
+            .unwrap()
+            .advance(1);
+        self.app.update();
+    }
+
+    // point the crosshair at a position in the gallery, the centre being 0, 0
+    pub fn move_cursor(&mut self, position: Vec2) {
+        let window = self
+            .app
+            .world
+            .get_resource::<Windows>()
+            .unwrap()
+            .get_primary()
+            .map(|window| Vec2::new(window.width(), window.height()) / 2.)
+            .unwrap();
+        self.send(CursorMoved {
+            id: WindowId::primary(),
+            position: position + window,
+        });
+    }
+
+    pub fn click(&mut self) {
+        self.press_button(MouseButton::Left);
+    }
+
+    pub fn right_click(&mut self) {
+        self.press_button(MouseButton::Right);
+    }
+
+    fn press_button(&mut self, button: MouseButton) {
+        for state in [ElementState::Pressed, ElementState::Released].iter() {
+            self.send(MouseButtonInput {
+                button,
+                state: *state,
+            });
+        }
+    }
+
+    pub fn press_key(&mut self, key: KeyCode) {
+        for state in [ElementState::Pressed, ElementState::Released].iter() {
+            self.send(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key),
+                state: *state,
+            });
+        }
+    }
+
+    fn send<T: Component>(&mut self, event: T) {
+        self.app
+            .world
+            .get_resource_mut::<Events<T>>()
+            .unwrap()
+            .send(event);
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app
+            .world
+            .get_resource::<State<GameState>>()
+            .unwrap()
+            .current()
+            .clone()
+    }
+
+    pub fn game(&self) -> &Game {
+        self.app.world.get_resource::<Game>().unwrap()
+    }
+
+    pub fn stats(&self) -> &Stats {
+        self.app.world.get_resource::<Stats>().unwrap()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.app.world.get_resource::<GameRng>().unwrap().seed()
+    }
+
+    // the round just played as a replay
+    pub fn replay(&self) -> Replay {
+        let world = &self.app.world;
+        Replay::new(
+            world.get_resource::<Recording>().unwrap(),
+            world.get_resource::<Level>().unwrap(),
+            *world.get_resource::<GameMode>().unwrap(),
+            *world.get_resource::<Difficulty>().unwrap(),
+            self.seed(),
+            self.game().score,
+        )
+    }
+
+    // play a replay's round, the replay feeding the input and moving the clock on
+    pub fn play_back(&mut self, replay: Replay) {
+        let frames = replay.frames().len() as u64;
+        // every frame either moves on by a step or feeds the input of a recorded frame
+        let updates = replay.end + frames + MAX_STEPS;
+        self.app.world.insert_resource(replay.difficulty());
+        self.app.world.insert_resource(Playback::new(replay));
+        for _ in 0..updates {
+            if !self.app.world.get_resource::<Playback>().unwrap().active() {
+                break;
+            }
+            self.app.update();
+        }
+    }
+
+    // where the standing targets worth shooting are, best first, leaving out those behind cover
+    pub fn targets(&mut self) -> Vec<Vec2> {
+        let mut targets: Vec<Standing> = self
+            .standing()
+            .into_iter()
+            .filter(|target| !matches!(target.kind, TargetKind::Penalty { .. }) && !target.covered)
+            .collect();
+        targets.sort_by_key(|target| std::cmp::Reverse(target.points));
+        targets
+            .into_iter()
+            .map(|target| target.position.truncate())
+            .collect()
+    }
+
+    // where a standing target can be shot, if it's out of cover with no other target in front
+    pub fn target(&mut self, name: &str) -> Option<Vec2> {
+        let standing = self.standing();
+        let target = standing
+            .iter()
+            .find(|target| target.name == name && !target.covered)?;
+        let blocked = standing.iter().any(|other| {
+            other.position.z > target.position.z
+                && other
+                    .position
+                    .truncate()
+                    .distance(target.position.truncate())
+                    < CLEARANCE
+        });
+        if blocked {
+            None
+        } else {
+            Some(target.position.truncate())
+        }
+    }
+
+    // step until a target can be shot, giving up when the round ends or after a while
+    pub fn wait_for(&mut self, name: &str) -> Option<Vec2> {
+        for _ in 0..MAX_STEPS {
+            if self.state() != GameState::Playing {
+                return None;
+            }
+            if let Some(position) = self.target(name) {
+                return Some(position);
+            }
+            self.step();
+        }
+        None
+    }
+
+    // the targets still up and whether a layer of the stall is drawn over each
+    fn standing(&mut self) -> Vec<Standing> {
+        let world = &mut self.app.world;
+        let covers: Vec<(GlobalTransform, TextureAtlasSprite, Handle<TextureAtlas>)> = world
+            .query_filtered::<(
+                &Transform,
+                Option<&Motion>,
+                &TextureAtlasSprite,
+                &Handle<TextureAtlas>,
+            ), With<Cover>>()
+            .iter(world)
+            .map(|(transform, motion, sprite, atlas)| {
+                let mut placed = *transform;
+                if let Some(motion) = motion {
+                    placed.translation = motion.current;
+                }
+                (placed.into(), sprite.clone(), atlas.clone())
+            })
+            .collect();
+        let mut standing = world.query_filtered::<(&Motion, &Target), Without<KnockedDown>>();
+        let masks = world.get_resource::<AlphaMasks>().unwrap();
+        let covered = |position: Vec3| {
+            covers.iter().any(|(placed, sprite, atlas)| {
+                placed.translation.z >= position.z
+                    && masks.get(atlas, sprite.index).is_some_and(|mask| {
+                        mask.contains(placed, sprite.flip_x, position.truncate())
+                    })
+            })
+        };
+        standing
+            .iter(world)
+            .map(|(motion, target)| Standing {
+                name: target.name.clone(),
+                kind: target.kind.clone(),
+                points: target.points,
+                position: motion.current,
+                covered: covered(motion.current),
+            })
+            .collect()
+    }
+}
+
+struct Standing {
+    name: String,
+    kind: TargetKind,
+    points: usize,
+    position: Vec3,
+    covered: bool,
+}
+
+// how a round without a window went
+pub struct RoundReport {
+    pub mode: GameMode,
+    pub seed: u64,
+    pub score: usize,
+    pub hits: usize,
+    pub shots: usize,
+    pub elapsed: usize,
+    // whether the round got to the results
+    pub finished: bool,
+    // the round played again from its recording, once it got to the results
+    pub playback: Option<PlaybackReport>,
+}
+
+impl RoundReport {
+    // whether the round got to the results on the same score twice
+    pub fn passed(&self) -> bool {
+        self.finished && self.playback.as_ref().is_some_and(PlaybackReport::matched)
+    }
+}
+
+// how a replay played back without a window went
+pub struct PlaybackReport {
+    pub mode: GameMode,
+    pub seed: u64,
+    pub score: usize,
+    pub recorded: usize,
+    pub finished: bool,
+}
+
+impl PlaybackReport {
+    // whether the replay got to the results on its recorded score
+    pub fn matched(&self) -> bool {
+        self.finished && self.score == self.recorded
+    }
+}
+
+// play a round with the bot, then play it back from its recording
+pub fn run(mode: GameMode, seed: Option<u64>) -> anyhow::Result<RoundReport> {
+    let mut simulation = Simulation::new(mode, seed)?;
+    simulation.play_round();
+    let game = simulation.game();
+    let stats = simulation.stats();
+    let mut report = RoundReport {
+        mode,
+        seed: simulation.seed(),
+        score: game.score,
+        hits: stats.hits,
+        shots: stats.shots,
+        elapsed: game.elapsed,
+        finished: simulation.state() == GameState::GameOver,
+        playback: None,
+    };
+    if report.finished {
+        report.playback = Some(play_back(simulation.replay())?);
+    }
+    Ok(report)
+}
+
+// play a replay without a window
+pub fn play_back(replay: Replay) -> anyhow::Result<PlaybackReport> {
+    let mode = replay.mode();
+    let recorded = replay.score;
+    let mut simulation = Simulation::new(mode, Some(replay.seed))?;
+    simulation.play_back(replay);
+    Ok(PlaybackReport {
+        mode,
+        seed: simulation.seed(),
+        score: simulation.game().score,
+        recorded,
+        finished: simulation.state() == GameState::GameOver,
+    })
+}
@@ -0,0 +1,174 @@
+use bevy::{asset::HandleId, prelude::*, sprite::Rect, utils::HashMap};
+use serde::Deserialize;
+
+const ALPHA_THRESHOLD: u8 = 128;
+
+// part of a target's hit area, shapes are relative to the centre of the target's face
+#[derive(Clone, Debug, Deserialize)]
+pub struct Zone {
+    pub name: String,
+    pub shape: Shape,
+    pub multiplier: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum Shape {
+    Circle {
+        #[serde(default)]
+        center: (f32, f32),
+        radius: f32,
+    },
+    Ellipse {
+        #[serde(default)]
+        center: (f32, f32),
+        radii: (f32, f32),
+    },
+    Rect {
+        #[serde(default)]
+        center: (f32, f32),
+        size: (f32, f32),
+    },
+}
+
+impl Shape {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Shape::Circle { center, radius } => {
+                point.distance(Vec2::new(center.0, center.1)) <= *radius
+            }
+            Shape::Ellipse { center, radii } => {
+                let offset = (point - Vec2::new(center.0, center.1)) / Vec2::new(radii.0, radii.1);
+                offset.length_squared() <= 1.
+            }
+            Shape::Rect { center, size } => {
+                let offset = (point - Vec2::new(center.0, center.1)).abs();
+                offset.x <= size.0 / 2. && offset.y <= size.1 / 2.
+            }
+        }
+    }
+}
+
+// the first zone containing the shot, so rings are listed from the bullseye outwards
+pub fn hit_zone(zones: &[Zone], shot: Vec2) -> Option<&Zone> {
+    zones.iter().find(|zone| zone.shape.contains(shot))
+}
+
+// which pixels of an atlas region are opaque enough to stop a shot
+pub struct AlphaMask {
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+}
+
+impl AlphaMask {
+    fn from_region(texture: &Texture, rect: &Rect) -> Self {
+        let width = rect.width() as usize;
+        let height = rect.height() as usize;
+        let stride = texture.size.width as usize * 4;
+        let mut bits = vec![0; (width * height).div_ceil(64)];
+        for y in 0..height {
+            for x in 0..width {
+                let alpha = stride * (rect.min.y as usize + y) + (rect.min.x as usize + x) * 4 + 3;
+                if texture.data.get(alpha).copied().unwrap_or(0) >= ALPHA_THRESHOLD {
+                    let bit = y * width + x;
+                    bits[bit / 64] |= 1 << (bit % 64);
+                }
+            }
+        }
+        AlphaMask {
+            width,
+            height,
+            bits,
+        }
+    }
+
+    // whether a shot lands on an opaque pixel of a sprite drawn with this region
+    pub fn contains(&self, sprite: &GlobalTransform, flip_x: bool, shot: Vec2) -> bool {
+        let local = sprite
+            .compute_matrix()
+            .inverse()
+            .transform_point3(shot.extend(sprite.translation.z));
+        let mut x = local.x + self.width as f32 / 2.;
+        if flip_x {
+            x = self.width as f32 - x;
+        }
+        let y = self.height as f32 / 2. - local.y;
+        if x < 0. || y < 0. || x >= self.width as f32 || y >= self.height as f32 {
+            return false;
+        }
+        let bit = y as usize * self.width + x as usize;
+        self.bits[bit / 64] & (1 << (bit % 64)) != 0
+    }
+}
+
+// alpha masks of every region in an atlas, rebuilt when the atlas texture changes
+#[derive(Default)]
+pub struct AlphaMasks {
+    atlases: HashMap<HandleId, Vec<AlphaMask>>,
+}
+
+impl AlphaMasks {
+    pub fn build(
+        &mut self,
+        handle: &Handle<TextureAtlas>,
+        atlas: &TextureAtlas,
+        texture: &Texture,
+    ) {
+        let mut masks = Vec::new();
+        // the masks read the alpha channel of 8 bit rgba pixels
+        if texture.format.pixel_size() == 4 {
+            for rect in atlas.textures.iter() {
+                masks.push(AlphaMask::from_region(texture, rect));
+            }
+        }
+        self.atlases.insert(handle.id, masks);
+    }
+
+    pub fn get(&self, atlas: &Handle<TextureAtlas>, index: u32) -> Option<&AlphaMask> {
+        self.atlases
+            .get(&atlas.id)
+            .and_then(|masks| masks.get(index as usize))
+    }
+
+    pub fn contains(&self, atlas: &Handle<TextureAtlas>) -> bool {
+        self.atlases.contains_key(&atlas.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_contains_its_edge() {
+        let circle = Shape::Circle {
+            center: (10., 0.),
+            radius: 5.,
+        };
+        assert!(circle.contains(Vec2::new(10., 0.)));
+        assert!(circle.contains(Vec2::new(15., 0.)));
+        assert!(!circle.contains(Vec2::new(14., 4.)));
+    }
+
+    #[test]
+    fn ellipse_scales_each_axis() {
+        let ellipse = Shape::Ellipse {
+            center: (0., 0.),
+            radii: (20., 10.),
+        };
+        assert!(ellipse.contains(Vec2::new(19., 0.)));
+        assert!(!ellipse.contains(Vec2::new(0., 11.)));
+        assert!(!ellipse.contains(Vec2::new(15., 8.)));
+    }
+
+    #[test]
+    fn rect_is_sized_around_its_center() {
+        let rect = Shape::Rect {
+            center: (0., 10.),
+            size: (20., 10.),
+        };
+        assert!(rect.contains(Vec2::new(-10., 15.)));
+        assert!(!rect.contains(Vec2::new(0., 0.)));
+        assert!(!rect.contains(Vec2::new(11., 10.)));
+    }
+}
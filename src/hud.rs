@@ -0,0 +1,309 @@
+use crate::{
+    digits::{self, Align, DigitDisplay, DigitFormat, Padding},
+    ghost::Ghost,
+    level::Level,
+    mode::GameMode,
+    AssetsReloaded, ComboConfig, Game, GameState, Textures, GHOST_LABEL, HOT_RELOAD_LABEL,
+};
+use bevy::prelude::*;
+
+pub struct Clock;
+pub struct Score;
+pub struct Streak;
+pub struct Multiplier;
+pub struct Life {
+    pub slot: usize,
+}
+pub struct ShotsLeft;
+pub struct Bullet {
+    pub slot: usize,
+}
+// points ahead of or behind the ghost
+pub struct GhostDelta;
+
+const AHEAD: Color = Color::rgb(0.3, 1., 0.3);
+const BEHIND: Color = Color::rgb(1., 0.3, 0.3);
+
+// the clock, score, combo, ammo, lives and the score against the ghost drawn over the gallery
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Countdown)
+                .with_system(setup_hud.system().after(GHOST_LABEL)),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Countdown)
+                .with_system(update_clock.system())
+                .with_system(update_score.system())
+                .with_system(update_combo.system())
+                .with_system(digits::render_digits.system())
+                .with_system(update_ammo.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(update_combo.system())
+                .with_system(respawn_hud.system().after(HOT_RELOAD_LABEL))
+                .with_system(update_clock.system())
+                .with_system(update_score.system())
+                .with_system(digits::render_digits.system())
+                .with_system(update_ammo.system())
+                .with_system(update_lives.system())
+                .with_system(update_ghost_delta.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::TimeUp)
+                .with_system(update_score.system())
+                .with_system(digits::render_digits.system()),
+        );
+    }
+}
+
+fn setup_hud(
+    mut commands: Commands,
+    texture: Res<Textures>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    ghost: Option<Res<Ghost>>,
+) {
+    let ghost = ghost.is_some_and(|ghost| ghost.running);
+    spawn_hud(&mut commands, &texture, &level, &mode, ghost);
+}
+
+// put the hud back after the gallery was rebuilt from changed assets
+fn respawn_hud(
+    mut commands: Commands,
+    mut events: EventReader<AssetsReloaded>,
+    texture: Res<Textures>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    ghost: Option<Res<Ghost>>,
+) {
+    if events.iter().count() > 0 {
+        let ghost = ghost.is_some_and(|ghost| ghost.running);
+        spawn_hud(&mut commands, &texture, &level, &mode, ghost);
+    }
+}
+
+pub fn spawn_hud(
+    commands: &mut Commands,
+    texture: &Textures,
+    level: &Level,
+    mode: &GameMode,
+    ghost: bool,
+) {
+    // setup timer
+    digits::spawn_digits(
+        commands,
+        Vec3::new(-600., 330., 3.),
+        DigitDisplay::new(DigitFormat::Clock, Padding::None, Align::Left),
+    )
+    .insert(Clock);
+    // setup score
+    commands.spawn_bundle(SpriteSheetBundle {
+        texture_atlas: texture.sprites_hud.atlas.clone(),
+        transform: Transform::from_xyz(400., 330., 3.),
+        sprite: TextureAtlasSprite {
+            index: texture.sprites_hud.index("text_score_small.png"),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands.spawn_bundle(SpriteSheetBundle {
+        texture_atlas: texture.sprites_hud.atlas.clone(),
+        transform: Transform::from_xyz(470., 330., 3.),
+        sprite: TextureAtlasSprite {
+            index: texture.sprites_hud.index("text_dots_small.png"),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    digits::spawn_digits(
+        commands,
+        Vec3::new(498., 330., 3.),
+        DigitDisplay::new(DigitFormat::Number, Padding::Zeros(4), Align::Left),
+    )
+    .insert(Score);
+    // setup streak and multiplier
+    commands.spawn_bundle(SpriteSheetBundle {
+        texture_atlas: texture.sprites_hud.atlas.clone(),
+        transform: Transform::from_xyz(430., 280., 3.),
+        sprite: TextureAtlasSprite {
+            index: texture.sprites_hud.index("icon_target.png"),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    digits::spawn_digits(
+        commands,
+        Vec3::new(466., 280., 3.),
+        DigitDisplay::new(DigitFormat::Number, Padding::None, Align::Left),
+    )
+    .insert(Streak);
+    digits::spawn_digits(
+        commands,
+        Vec3::new(582., 280., 3.),
+        DigitDisplay::new(DigitFormat::Times, Padding::None, Align::Right),
+    )
+    .insert(Multiplier);
+    // setup score against the ghost
+    if ghost {
+        commands.spawn_bundle(SpriteSheetBundle {
+            texture_atlas: texture.sprites_hud.atlas.clone(),
+            transform: Transform::from_xyz(430., 230., 3.),
+            sprite: TextureAtlasSprite {
+                index: texture.sprites_hud.index("crosshair_blue_small.png"),
+                color: Color::rgba(1., 1., 1., 0.6),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        digits::spawn_digits(
+            commands,
+            Vec3::new(466., 230., 3.),
+            DigitDisplay::new(DigitFormat::Plus, Padding::None, Align::Left),
+        )
+        .insert(GhostDelta);
+    }
+    // setup ammo
+    for slot in 0..level.ammo {
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: texture.sprites_hud.atlas.clone(),
+                transform: Transform::from_xyz(-600. + slot as f32 * 28., 270., 3.),
+                sprite: TextureAtlasSprite {
+                    index: texture.sprites_hud.index("icon_bullet_gold_long.png"),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Bullet { slot });
+    }
+    if mode.shots().is_some() {
+        digits::spawn_digits(
+            commands,
+            Vec3::new(-600. + level.ammo as f32 * 28. + 20., 270., 3.),
+            DigitDisplay::new(DigitFormat::Times, Padding::None, Align::Left),
+        )
+        .insert(ShotsLeft);
+    }
+    // setup lives
+    for slot in 0..mode.lives().unwrap_or(0) {
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: texture.sprites_hud.atlas.clone(),
+                transform: Transform::from_xyz(-600. + slot as f32 * 40., 215., 3.),
+                sprite: TextureAtlasSprite {
+                    index: texture.sprites_hud.index("icon_duck.png"),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Life { slot });
+    }
+}
+
+fn update_clock(
+    mut query: Query<&mut DigitDisplay, With<Clock>>,
+    game: Res<Game>,
+    mode: Res<GameMode>,
+) {
+    let seconds = if mode.timed() {
+        game.time_left
+    } else {
+        game.elapsed
+    };
+    for mut display in query.iter_mut() {
+        // only touch the display when the glyphs need to change
+        if display.value != seconds {
+            display.value = seconds;
+        }
+    }
+}
+
+fn update_score(mut query: Query<&mut DigitDisplay, With<Score>>, game: Res<Game>) {
+    for mut display in query.iter_mut() {
+        if display.value != game.score {
+            display.value = game.score;
+        }
+    }
+}
+
+fn update_combo(
+    mut displays: QuerySet<(
+        Query<&mut DigitDisplay, With<Streak>>,
+        Query<&mut DigitDisplay, With<Multiplier>>,
+    )>,
+    game: Res<Game>,
+    combo: Res<ComboConfig>,
+) {
+    // the hits counting towards the multiplier, so both drop together when the combo runs out
+    for mut display in displays.q0_mut().iter_mut() {
+        if display.value != game.combo {
+            display.value = game.combo;
+        }
+    }
+    for mut display in displays.q1_mut().iter_mut() {
+        let multiplier = game.multiplier(&combo);
+        if display.value != multiplier {
+            display.value = multiplier;
+        }
+    }
+}
+
+fn update_lives(mut query: Query<(&mut Visible, &Life)>, game: Res<Game>) {
+    for (mut visible, life) in query.iter_mut() {
+        visible.is_visible = game.lives.is_some_and(|lives| life.slot < lives);
+    }
+}
+
+fn update_ammo(
+    mut query: Query<(&mut TextureAtlasSprite, &Bullet)>,
+    mut shots_left: Query<&mut DigitDisplay, With<ShotsLeft>>,
+    game: Res<Game>,
+    texture: Res<Textures>,
+) {
+    for mut display in shots_left.iter_mut() {
+        let shots = game.shots_left.unwrap_or(0);
+        if display.value != shots {
+            display.value = shots;
+        }
+    }
+    for (mut sprite, bullet) in query.iter_mut() {
+        if bullet.slot < game.ammo {
+            sprite.index = texture.sprites_hud.index("icon_bullet_gold_long.png");
+        } else {
+            sprite.index = texture.sprites_hud.index("icon_bullet_empty_long.png");
+        }
+    }
+}
+
+// points ahead of the ghost in green after a plus, points behind it in red
+fn update_ghost_delta(
+    mut query: Query<&mut DigitDisplay, With<GhostDelta>>,
+    game: Res<Game>,
+    ghost: Option<Res<Ghost>>,
+) {
+    let ghost = match ghost {
+        Some(ghost) => ghost,
+        None => return,
+    };
+    let ahead = game.score >= ghost.score;
+    let (value, color) = if ahead {
+        (game.score - ghost.score, AHEAD)
+    } else {
+        (ghost.score - game.score, BEHIND)
+    };
+    for mut display in query.iter_mut() {
+        if display.value != value || display.color != color {
+            display.value = value;
+            display.color = color;
+            display.format = if ahead {
+                DigitFormat::Plus
+            } else {
+                DigitFormat::Number
+            };
+        }
+    }
+}
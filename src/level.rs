@@ -0,0 +1,249 @@
+use crate::{
+    atlas::SpriteSheet,
+    hit::Zone,
+    movement::{MovementPattern, Pause},
+    waves::Waves,
+};
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// a level layout: the stall scenery, the targets and the rules of the round
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "2b8f0d7e-94c1-4a3e-b6d2-5e1c8a9f7b30"]
+pub struct Level {
+    // also names the level's high score tables
+    pub name: String,
+    pub round_time: usize,
+    pub ammo: usize,
+    // score needed for each grade, best first, the round is lost below the last one
+    #[serde(default)]
+    pub grades: Vec<Grade>,
+    // targets moving off one end of the track come back in at the other
+    #[serde(default = "default_track")]
+    pub track: (f32, f32),
+    // named hit areas shared by targets of the same type
+    pub hit_areas: HashMap<String, Vec<Zone>>,
+    pub layers: Vec<Layer>,
+    pub targets: Vec<TargetSpawn>,
+    // targets named in the waves only come in with them, the others are up from the start
+    #[serde(default)]
+    pub waves: Option<Waves>,
+    // plays every round of the level the same instead of changing daily
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Level {
+    // hit areas of the targets are checked when the level is loaded
+    pub fn hit_area(&self, name: &str) -> &[Zone] {
+        self.hit_areas
+            .get(name)
+            .map_or(&[], |zones| zones.as_slice())
+    }
+
+    // catch mistakes a level file can make besides its syntax
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.track.0 >= self.track.1 {
+            anyhow::bail!("the track needs to start before it ends");
+        }
+        for target in self.targets.iter() {
+            if let Err(error) = target.movement.check() {
+                anyhow::bail!("movement of target {}: {}", target.name, error);
+            }
+            if let Some(Err(error)) = target.pause.as_ref().map(Pause::check) {
+                anyhow::bail!("pause of target {}: {}", target.name, error);
+            }
+            if target.respawn_time < 0. {
+                anyhow::bail!("respawn time of target {} can't be negative", target.name);
+            }
+            if !self.hit_areas.contains_key(&target.hit_area) {
+                anyhow::bail!(
+                    "hit area {} of target {} is not in the level",
+                    target.hit_area,
+                    target.name
+                );
+            }
+        }
+        if let Some(waves) = &self.waves {
+            if let Err(error) = waves.check() {
+                anyhow::bail!("waves: {}", error);
+            }
+            let names = waves.groups.iter().flat_map(|group| group.targets.iter());
+            for name in names {
+                if self.target(name).is_none() {
+                    anyhow::bail!("target {} of the waves is not in the level", name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // make sure every sprite the level names is in the spritesheets
+    pub fn check_sprites(&self, stall: &SpriteSheet, objects: &SpriteSheet) -> anyhow::Result<()> {
+        let stall_sprites = self.layers.iter().map(|layer| &layer.sprite);
+        let object_sprites = self
+            .layers
+            .iter()
+            .filter_map(|layer| layer.decal.clone())
+            .chain(self.targets.iter().flat_map(|target| {
+                let mut sprites = vec![target.face.clone()];
+                if let Some(stick) = &target.stick {
+                    sprites.push(stick.sprite.clone());
+                    sprites.push(stick.broken_sprite());
+                }
+                sprites
+            }));
+        for sprite in stall_sprites {
+            if stall.get(sprite).is_none() {
+                anyhow::bail!("sprite {} is not in the stall spritesheet", sprite);
+            }
+        }
+        for sprite in object_sprites {
+            if objects.get(&sprite).is_none() {
+                anyhow::bail!("sprite {} is not in the objects spritesheet", sprite);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn target(&self, name: &str) -> Option<&TargetSpawn> {
+        self.targets.iter().find(|target| target.name == name)
+    }
+
+    pub fn grade(&self, score: usize) -> Option<&Grade> {
+        self.grades.iter().find(|grade| score >= grade.score)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Grade {
+    pub name: String,
+    pub score: usize,
+}
+
+// a sprite from the stall spritesheet, optionally repeated along a step
+#[derive(Clone, Debug, Deserialize)]
+pub struct Layer {
+    pub sprite: String,
+    pub position: (f32, f32),
+    pub z: f32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub repeat: Option<Repeat>,
+    #[serde(default)]
+    pub scroll: Option<Scroll>,
+    // stops shots at targets drawn behind it
+    #[serde(default)]
+    pub solid: bool,
+    // sprite from the objects spritesheet left where a solid layer is shot
+    #[serde(default)]
+    pub decal: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Repeat {
+    pub count: usize,
+    pub step: (f32, f32),
+}
+
+// horizontal drift of a layer, wrapping around between min and max
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scroll {
+    pub speed: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+// a target from the objects spritesheet
+#[derive(Clone, Debug, Deserialize)]
+pub struct TargetSpawn {
+    // shown in the round statistics
+    pub name: String,
+    pub face: String,
+    #[serde(default)]
+    pub stick: Option<Stick>,
+    pub position: (f32, f32),
+    pub z: f32,
+    pub movement: MovementPattern,
+    #[serde(default)]
+    pub pause: Option<Pause>,
+    // scaled by the multiplier of the zone that was hit
+    pub points: usize,
+    pub hit_area: String,
+    // test shots against the opaque pixels of the face instead of only the hit area
+    #[serde(default)]
+    pub pixel_perfect: bool,
+    pub respawn_time: f32,
+    #[serde(default)]
+    pub kind: TargetKind,
+}
+
+// what else happens when a target is hit, besides scoring its points
+#[derive(Clone, Debug, Default, Deserialize)]
+pub enum TargetKind {
+    #[default]
+    Normal,
+    // a target not to shoot, costing points and seconds instead of scoring
+    Penalty {
+        points: usize,
+        time: usize,
+    },
+    // adds seconds to the clock
+    TimeBonus {
+        seconds: usize,
+    },
+    // fills the magazine without reloading
+    AmmoRefill,
+    // drawn in gold, its rarity and worth come from its movement and points
+    Golden,
+}
+
+// the stick a target is mounted on, its broken sprite is the same name with a "_broken" suffix
+#[derive(Clone, Debug, Deserialize)]
+pub struct Stick {
+    pub sprite: String,
+    pub offset: (f32, f32),
+}
+
+impl Stick {
+    pub fn broken_sprite(&self) -> String {
+        format!("{}_broken.png", self.sprite.trim_end_matches(".png"))
+    }
+}
+
+fn default_scale() -> f32 {
+    1.
+}
+
+fn default_track() -> (f32, f32) {
+    (-650., 650.)
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let level: Level = ron::de::from_bytes(bytes)?;
+            level.check()?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
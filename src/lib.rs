@@ -0,0 +1,483 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+pub mod atlas;
+pub mod clock;
+pub mod digits;
+pub mod ghost;
+pub mod headless;
+pub mod hit;
+pub mod hud;
+pub mod level;
+pub mod menu;
+pub mod mode;
+pub mod movement;
+pub mod replay;
+pub mod results;
+pub mod rifle;
+pub mod rng;
+pub mod round;
+pub mod scores;
+pub mod scoring;
+pub mod stall;
+pub mod targets;
+pub mod waves;
+
+use atlas::{SpriteSheet, SpriteSheetLoader};
+use bevy::{
+    app::PluginGroupBuilder, ecs::schedule::ShouldRun, prelude::*, render::camera::Camera,
+    utils::Duration,
+};
+use clock::SimulationClock;
+use ghost::GhostPlugin;
+use hit::AlphaMasks;
+use hud::HudPlugin;
+use level::{Level, LevelLoader};
+use menu::MenuPlugin;
+use mode::GameMode;
+use replay::{Recording, ReplayPlugin, RoundSteps};
+use results::ResultsPlugin;
+use rifle::{RiflePlugin, Shot};
+use rng::GameRng;
+use round::RoundPlugin;
+use scoring::{ScoringPlugin, ShotOutcome};
+use stall::StallPlugin;
+use std::collections::BTreeMap;
+use targets::TargetsPlugin;
+use waves::Difficulty;
+
+pub const LEVEL: &str = "levels/gallery.level.ron";
+const ASSETS: &str = "assets";
+// stage running the game's simulation once for every fixed step owed, before the update stage
+pub const SIMULATION_STAGE: &str = "simulation";
+// labels ordering shots from the rifle to their outcome within a frame
+pub const FIRE_LABEL: &str = "fire";
+pub const RESOLVE_LABEL: &str = "resolve";
+pub const SCORE_LABEL: &str = "score";
+pub const HOT_RELOAD_LABEL: &str = "hot_reload";
+// systems drawing from the GameRng when a round starts run after it's reseeded
+pub const SEED_LABEL: &str = "seed";
+// systems showing the ghost when a round starts run after its run is loaded
+pub const GHOST_LABEL: &str = "ghost";
+
+pub struct AssetHandles {
+    pub stall: Handle<SpriteSheet>,
+    pub hud: Handle<SpriteSheet>,
+    pub objects: Handle<SpriteSheet>,
+    pub level: Handle<Level>,
+}
+pub struct Textures {
+    pub sprites_stall: SpriteSheet,
+    pub sprites_hud: SpriteSheet,
+    pub sprites_objects: SpriteSheet,
+}
+impl Textures {
+    pub fn digit(&self, digit: u32) -> u32 {
+        self.sprites_hud.index(&format!("text_{}_small.png", digit))
+    }
+}
+#[derive(Default)]
+pub struct Game {
+    pub score: usize,
+    pub time_left: usize,
+    pub ammo: usize,
+    pub reload: Option<Timer>,
+    pub last_mouse: Vec2,
+    // hits since the last miss, counting towards the multiplier until the window runs out
+    pub combo: usize,
+    pub combo_window: Option<Timer>,
+    // simulation steps played, and the whole seconds they add up to
+    pub ticks: u64,
+    // shown instead of the time left when the mode has no clock
+    pub elapsed: usize,
+    pub lives: Option<usize>,
+    // bullets left for the round, magazine included, when the mode limits them
+    pub shots_left: Option<usize>,
+}
+impl Game {
+    // score multiplier of the next hit
+    pub fn multiplier(&self, config: &ComboConfig) -> usize {
+        (1 + self.combo / config.step).min(config.max_multiplier)
+    }
+
+    // bullets a reload puts in the magazine
+    pub fn magazine(&self, level: &Level) -> usize {
+        self.shots_left
+            .map_or(level.ammo, |shots| shots.min(level.ammo))
+    }
+
+    pub fn round_over(&self, mode: &GameMode) -> bool {
+        (mode.timed() && self.time_left == 0) || self.lives == Some(0) || self.shots_left == Some(0)
+    }
+
+    pub fn lose_life(&mut self) {
+        if let Some(lives) = self.lives.as_mut() {
+            *lives = lives.saturating_sub(1);
+        }
+    }
+
+    pub fn break_combo(&mut self) {
+        self.combo = 0;
+        self.combo_window = None;
+    }
+
+    // count a hit towards the combo and give the next one a fresh window
+    pub fn extend_combo(&mut self, config: &ComboConfig) {
+        self.combo += 1;
+        self.combo_window = Some(Timer::from_seconds(config.window, false));
+    }
+
+    // end the combo once its window runs out without a hit
+    pub fn decay_combo(&mut self, delta: Duration) {
+        let expired = match self.combo_window.as_mut() {
+            Some(timer) => timer.tick(delta).finished(),
+            None => false,
+        };
+        if expired {
+            self.break_combo();
+        }
+    }
+}
+
+// how hits in a row raise the score multiplier, insert it before adding the plugins to change the rules
+pub struct ComboConfig {
+    // consecutive hits needed to raise the multiplier by one
+    pub step: usize,
+    pub max_multiplier: usize,
+    // seconds allowed between hits before the combo runs out
+    pub window: f32,
+}
+impl Default for ComboConfig {
+    fn default() -> Self {
+        ComboConfig {
+            step: 3,
+            max_multiplier: 4,
+            window: 3.,
+        }
+    }
+}
+
+// what happened during the round, for the results screen
+#[derive(Default)]
+pub struct Stats {
+    pub shots: usize,
+    pub hits: usize,
+    pub streak: usize,
+    pub best_streak: usize,
+    pub best_multiplier: usize,
+    pub penalties: usize,
+    pub hits_by_target: BTreeMap<String, usize>,
+    pub points_by_zone: BTreeMap<String, usize>,
+}
+impl Stats {
+    pub fn hit(&mut self, target: &str, zone: &str, points: usize, multiplier: usize) {
+        self.shots += 1;
+        self.hits += 1;
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        self.best_multiplier = self.best_multiplier.max(multiplier);
+        *self.hits_by_target.entry(target.to_string()).or_default() += 1;
+        *self.points_by_zone.entry(zone.to_string()).or_default() += points;
+    }
+
+    pub fn miss(&mut self) {
+        self.shots += 1;
+        self.streak = 0;
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            return 0.;
+        }
+        self.hits as f32 / self.shots as f32 * 100.
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum GameState {
+    Loading,
+    MainMenu,
+    Settings,
+    Countdown,
+    Playing,
+    Paused,
+    TimeUp,
+    GameOver,
+    Leaderboard,
+}
+
+// sent once the gallery was cleared after its assets changed on disk, each plugin spawns its own part again
+pub struct AssetsReloaded;
+
+// everything that makes up the gallery, any of it but GalleryPlugin can be disabled or replaced when embedding:
+// the hud, menus and results screen need a renderer and ui, the rest runs without a window
+pub struct GalleryPlugins;
+
+impl PluginGroup for GalleryPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(GalleryPlugin)
+            .add(RoundPlugin)
+            .add(StallPlugin)
+            .add(TargetsPlugin)
+            .add(RiflePlugin)
+            .add(ScoringPlugin)
+            .add(ReplayPlugin)
+            .add(GhostPlugin)
+            .add(HudPlugin)
+            .add(MenuPlugin)
+            .add(ResultsPlugin);
+    }
+}
+
+// what the other plugins build on: the shared resources, loading, the game state and the simulation stage
+pub struct GalleryPlugin;
+
+impl Plugin for GalleryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Game>()
+            .init_resource::<Stats>()
+            .init_resource::<GameMode>()
+            .init_resource::<Difficulty>()
+            .init_resource::<SimulationClock>()
+            .init_resource::<GameRng>()
+            .init_resource::<AlphaMasks>()
+            .init_resource::<ComboConfig>()
+            // read by the results and the ghost whether or not rounds are recorded
+            .init_resource::<RoundSteps>()
+            .init_resource::<Recording>()
+            // sent and read by different plugins, so either end can be replaced
+            .add_event::<Shot>()
+            .add_event::<ShotOutcome>()
+            .add_event::<AssetsReloaded>()
+            .add_asset::<SpriteSheet>()
+            .init_asset_loader::<SpriteSheetLoader>()
+            .add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_state(GameState::Loading)
+            .add_stage_before(
+                CoreStage::Update,
+                SIMULATION_STAGE,
+                SystemStage::parallel().with_run_criteria(clock::run_steps.system()),
+            )
+            .add_startup_system(load_assets.system())
+            .add_system_set(
+                SystemSet::on_update(GameState::Loading).with_system(check_assets.system()),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(teardown.system()))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Countdown)
+                    .with_system(teardown.system())
+                    .with_system(seed_round.system().label(SEED_LABEL)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(teardown.system()))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Paused).with_system(clock::settle_motion.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::TimeUp).with_system(clock::settle_motion.system()),
+            )
+            .add_system(hot_reload.system().label(HOT_RELOAD_LABEL))
+            .add_system(clock::interpolate_motion.system());
+    }
+}
+
+// systems run once per simulation step while in the given state
+pub fn step_set(state: GameState) -> SystemSet {
+    SystemSet::new().with_run_criteria(
+        (move |current: Res<State<GameState>>| {
+            if *current.current() == state {
+                ShouldRun::Yes
+            } else {
+                ShouldRun::No
+            }
+        })
+        .system(),
+    )
+}
+
+// where to load the assets from: bevy looks beside Cargo.toml under cargo and beside the executable
+// otherwise, so a game started some other way falls back to the working directory
+pub fn asset_folder() -> String {
+    let beside_executable = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(ASSETS)));
+    if std::env::var_os("CARGO_MANIFEST_DIR").is_some()
+        || beside_executable.is_some_and(|dir| dir.is_dir())
+    {
+        return ASSETS.to_string();
+    }
+    std::env::current_dir()
+        .ok()
+        .map(|dir| dir.join(ASSETS))
+        .filter(|dir| dir.is_dir())
+        .map_or_else(
+            || ASSETS.to_string(),
+            |dir| dir.to_string_lossy().into_owned(),
+        )
+}
+
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AssetHandles {
+        stall: asset_server.load("textures/spritesheet_stall.xml"),
+        hud: asset_server.load("textures/spritesheet_hud.xml"),
+        objects: asset_server.load("textures/spritesheet_objects.xml"),
+        level: asset_server.load(LEVEL),
+    });
+}
+
+// wait for every spritesheet and the level to be parsed before starting the first round,
+// a level naming sprites the sheets don't have waits to be fixed on disk
+fn check_assets(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    mut reported: Local<Option<String>>,
+    handles: Res<AssetHandles>,
+    sheets: Res<Assets<SpriteSheet>>,
+    levels: Res<Assets<Level>>,
+) {
+    if let (Some(stall), Some(hud), Some(objects), Some(level)) = (
+        sheets.get(&handles.stall),
+        sheets.get(&handles.hud),
+        sheets.get(&handles.objects),
+        levels.get(&handles.level),
+    ) {
+        if let Err(error) = level.check_sprites(stall, objects) {
+            let error = error.to_string();
+            if reported.as_ref() != Some(&error) {
+                warn!("can't start the level: {}", error);
+                *reported = Some(error);
+            }
+            return;
+        }
+        commands.insert_resource(Textures {
+            sprites_stall: stall.clone(),
+            sprites_hud: hud.clone(),
+            sprites_objects: objects.clone(),
+        });
+        commands.insert_resource(level.clone());
+        state.set(GameState::MainMenu).unwrap();
+    }
+}
+
+fn seed_round(mut rng: ResMut<GameRng>, level: Res<Level>) {
+    rng.reseed(&level);
+}
+
+// take in a spritesheet or the level changed on disk whatever screen is up, and clear the gallery once
+// the round is being played, for every part to rebuild itself in place on the AssetsReloaded event
+fn hot_reload(
+    mut commands: Commands,
+    mut sheet_events: EventReader<AssetEvent<SpriteSheet>>,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    mut reloaded_events: EventWriter<AssetsReloaded>,
+    mut rebuild: Local<bool>,
+    state: Res<State<GameState>>,
+    handles: Option<Res<AssetHandles>>,
+    sheets: Res<Assets<SpriteSheet>>,
+    levels: Res<Assets<Level>>,
+    texture: Option<ResMut<Textures>>,
+    level: Option<ResMut<Level>>,
+    mut game: ResMut<Game>,
+    mut masks: ResMut<AlphaMasks>,
+    entities: Query<Entity, Without<Camera>>,
+) {
+    // still loading, check_assets picks up the latest assets
+    let (handles, mut texture, mut level) = match (handles, texture, level) {
+        (Some(handles), Some(texture), Some(level)) => (handles, texture, level),
+        _ => return,
+    };
+    let mut changed = false;
+    for event in sheet_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            changed |=
+                *handle == handles.stall || *handle == handles.hud || *handle == handles.objects;
+        }
+    }
+    for event in level_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            changed |= *handle == handles.level;
+        }
+    }
+    if changed {
+        // the level and spritesheets have to agree, otherwise the ones in use stay
+        if let (Some(stall), Some(hud), Some(objects), Some(reloaded)) = (
+            sheets.get(&handles.stall),
+            sheets.get(&handles.hud),
+            sheets.get(&handles.objects),
+            levels.get(&handles.level),
+        ) {
+            match reloaded.check_sprites(stall, objects) {
+                Ok(()) => {
+                    texture.sprites_stall = stall.clone();
+                    texture.sprites_hud = hud.clone();
+                    texture.sprites_objects = objects.clone();
+                    *masks = AlphaMasks::default();
+                    *level = reloaded.clone();
+                    game.ammo = game.ammo.min(game.magazine(&level));
+                    *rebuild = true;
+                }
+                Err(error) => warn!("keeping the previous level and spritesheets: {}", error),
+            }
+        }
+    }
+
+    match state.current() {
+        GameState::Playing if *rebuild => *rebuild = false,
+        // the round picks the changes up once it's played again
+        GameState::Playing | GameState::Countdown | GameState::Paused => return,
+        // settings are always pushed over another screen, the pause menu of a round among them
+        GameState::Settings if state.inactives().contains(&GameState::Playing) => return,
+        // the next round is built from the new assets
+        _ => {
+            *rebuild = false;
+            return;
+        }
+    }
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    reloaded_events.send(AssetsReloaded);
+}
+
+// remove all entities that are not a camera
+pub fn teardown(mut commands: Commands, entities: Query<Entity, Without<Camera>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplier_rises_every_step_up_to_its_max() {
+        let config = ComboConfig::default();
+        let mut game = Game::default();
+        let mut multipliers = Vec::new();
+        for _ in 0..12 {
+            multipliers.push(game.multiplier(&config));
+            game.extend_combo(&config);
+        }
+        assert_eq!(multipliers, [1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 4]);
+        assert_eq!(game.multiplier(&config), 4);
+    }
+
+    #[test]
+    fn combo_runs_out_without_a_hit() {
+        let config = ComboConfig::default();
+        let mut game = Game::default();
+        game.extend_combo(&config);
+        game.extend_combo(&config);
+        game.decay_combo(Duration::from_secs_f32(config.window - 0.5));
+        assert_eq!(game.combo, 2);
+        // a hit starts the window over
+        game.extend_combo(&config);
+        game.decay_combo(Duration::from_secs_f32(config.window - 0.5));
+        assert_eq!(game.combo, 3);
+        game.decay_combo(Duration::from_secs_f32(0.5));
+        assert_eq!(game.combo, 0);
+        assert_eq!(game.multiplier(&config), 1);
+        assert!(game.combo_window.is_none());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::{level::Level, mode::GameMode, scores::HighScores, waves::Difficulty, GameState};
+use bevy::{app::AppExit, prelude::*, window::WindowMode};
+
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuAction {
+    Play,
+    Mode,
+    Difficulty,
+    Resume,
+    Finish,
+    Restart,
+    Settings,
+    Leaderboard,
+    Fullscreen,
+    Vsync,
+    Ghost,
+    Back,
+    MainMenu,
+    Quit,
+}
+
+impl MenuAction {
+    fn label(&self, settings: &Settings, mode: &GameMode, difficulty: &Difficulty) -> String {
+        match self {
+            MenuAction::Play => "Play".to_string(),
+            MenuAction::Mode => format!("Mode: {}", mode.name()),
+            MenuAction::Difficulty => format!("Difficulty: {}", difficulty.name()),
+            MenuAction::Resume => "Resume".to_string(),
+            MenuAction::Finish => "Finish Round".to_string(),
+            MenuAction::Restart => "Restart".to_string(),
+            MenuAction::Settings => "Settings".to_string(),
+            MenuAction::Leaderboard => "High Scores".to_string(),
+            MenuAction::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            MenuAction::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+            MenuAction::Ghost => format!("Ghost: {}", on_off(settings.ghost)),
+            MenuAction::Back => "Back".to_string(),
+            MenuAction::MainMenu => "Quit to Menu".to_string(),
+            MenuAction::Quit => "Quit".to_string(),
+        }
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+// window options changed from the settings screen
+pub struct Settings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    // race the best run of the level, mode and difficulty
+    pub ghost: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            fullscreen: false,
+            vsync: true,
+            ghost: true,
+        }
+    }
+}
+
+pub struct MenuMaterials {
+    background: Handle<ColorMaterial>,
+    overlay: Handle<ColorMaterial>,
+    normal: Handle<ColorMaterial>,
+    selected: Handle<ColorMaterial>,
+}
+
+impl FromWorld for MenuMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+        MenuMaterials {
+            background: materials.add(Color::rgb(0.12, 0.1, 0.1).into()),
+            overlay: materials.add(Color::rgba(0., 0., 0., 0.6).into()),
+            normal: materials.add(Color::rgb(0.45, 0.25, 0.15).into()),
+            selected: materials.add(Color::rgb(0.8, 0.2, 0.15).into()),
+        }
+    }
+}
+
+// the main, pause, settings and high score menus
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Settings>()
+            .init_resource::<MenuSelection>()
+            .init_resource::<MenuMaterials>()
+            .init_resource::<HighScores>();
+        for state in [
+            GameState::MainMenu,
+            GameState::Settings,
+            GameState::Paused,
+            GameState::Leaderboard,
+        ]
+        .iter()
+        {
+            // a screen opened over another one takes its place until it's closed
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(setup_menu.system()))
+                .add_system_set(
+                    SystemSet::on_exit(state.clone()).with_system(despawn_menu.system()),
+                )
+                .add_system_set(
+                    SystemSet::on_pause(state.clone()).with_system(despawn_menu.system()),
+                )
+                .add_system_set(
+                    SystemSet::on_resume(state.clone()).with_system(setup_menu.system()),
+                );
+        }
+        app.add_system(menu_input.system())
+            .add_system(highlight_menu.system());
+    }
+}
+
+// the item picked with the keyboard or last hovered with the mouse
+#[derive(Default)]
+pub struct MenuSelection(usize);
+
+// root of a menu screen, despawned when leaving it
+pub struct Menu;
+pub struct MenuItem {
+    index: usize,
+    action: MenuAction,
+}
+
+// build the screen of whichever menu state was just entered
+fn setup_menu(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    settings: Res<Settings>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    high_scores: Res<HighScores>,
+    level: Res<Level>,
+    mut selection: ResMut<MenuSelection>,
+    mut windows: ResMut<Windows>,
+) {
+    let mut lines = Vec::new();
+    let (title, items, background) = match state.current() {
+        GameState::MainMenu => (
+            "Take a Shot!",
+            vec![
+                MenuAction::Play,
+                MenuAction::Mode,
+                MenuAction::Difficulty,
+                MenuAction::Settings,
+                MenuAction::Leaderboard,
+                MenuAction::Quit,
+            ],
+            materials.background.clone(),
+        ),
+        GameState::Leaderboard => {
+            lines.push(format!(
+                "{} - {} - {}",
+                level.name,
+                mode.name(),
+                difficulty.name()
+            ));
+            let table = high_scores.table(&level.name, mode.key(), difficulty.key());
+            for (rank, entry) in table.iter().enumerate() {
+                lines.push(format!(
+                    "{}. {}   {}",
+                    rank + 1,
+                    entry.initials,
+                    entry.score
+                ));
+            }
+            if table.is_empty() {
+                lines.push("No scores yet".to_string());
+            }
+            (
+                "High Scores",
+                vec![MenuAction::Back],
+                materials.background.clone(),
+            )
+        }
+        GameState::Paused => {
+            let mut items = vec![MenuAction::Resume, MenuAction::Restart];
+            // rounds without a clock or lives only end when asked to
+            if !mode.ranked() {
+                items.push(MenuAction::Finish);
+            }
+            items.extend([MenuAction::Settings, MenuAction::MainMenu].iter());
+            ("Paused", items, materials.overlay.clone())
+        }
+        GameState::Settings => (
+            "Settings",
+            vec![
+                MenuAction::Fullscreen,
+                MenuAction::Vsync,
+                MenuAction::Ghost,
+                MenuAction::Back,
+            ],
+            materials.overlay.clone(),
+        ),
+        _ => return,
+    };
+    selection.0 = 0;
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_cursor_visibility(true);
+    }
+
+    let font = asset_server.load(FONT);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: background,
+            ..Default::default()
+        })
+        .insert(Menu)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: Rect::all(Val::Px(24.)),
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    title,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 64.,
+                        color: Color::WHITE,
+                    },
+                    Default::default(),
+                ),
+                ..Default::default()
+            });
+            for line in lines {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        line,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 32.,
+                            color: Color::WHITE,
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                });
+            }
+            for (index, action) in items.into_iter().enumerate() {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(320.), Val::Px(56.)),
+                            margin: Rect::all(Val::Px(8.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        material: materials.normal.clone(),
+                        ..Default::default()
+                    })
+                    .insert(MenuItem { index, action })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                action.label(&settings, &mode, &difficulty),
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 32.,
+                                    color: Color::WHITE,
+                                },
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        });
+                    });
+            }
+        });
+}
+
+fn despawn_menu(mut commands: Commands, menus: Query<Entity, With<Menu>>) {
+    for entity in menus.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// move the selection with the arrow keys or the mouse, and act on enter, space or a click
+fn menu_input(
+    mut state: ResMut<State<GameState>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut selection: ResMut<MenuSelection>,
+    mut settings: ResMut<Settings>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut windows: ResMut<Windows>,
+    mut exit: EventWriter<AppExit>,
+    items: Query<&MenuItem>,
+    interactions: Query<(&MenuItem, &Interaction), Changed<Interaction>>,
+) {
+    let count = items.iter().count();
+    if count == 0 {
+        return;
+    }
+
+    let mut activated = None;
+    for (item, interaction) in interactions.iter() {
+        match interaction {
+            Interaction::Clicked => activated = Some(item.action),
+            Interaction::Hovered => selection.0 = item.index,
+            Interaction::None => {}
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) || keyboard_input.just_pressed(KeyCode::W) {
+        selection.0 = (selection.0 + count - 1) % count;
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) || keyboard_input.just_pressed(KeyCode::S) {
+        selection.0 = (selection.0 + 1) % count;
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) || keyboard_input.just_pressed(KeyCode::Space) {
+        activated = items
+            .iter()
+            .find(|item| item.index == selection.0)
+            .map(|item| item.action);
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        activated = match state.current() {
+            GameState::Paused => Some(MenuAction::Resume),
+            GameState::Settings | GameState::Leaderboard => Some(MenuAction::Back),
+            _ => activated,
+        };
+    }
+    let action = match activated {
+        Some(action) => action,
+        None => return,
+    };
+    // the next screen shouldn't see the key that opened it
+    for key in [KeyCode::Return, KeyCode::Space, KeyCode::Escape].iter() {
+        keyboard_input.reset(*key);
+    }
+
+    match action {
+        MenuAction::Play => state.set(GameState::Countdown).unwrap(),
+        MenuAction::Mode => *mode = mode.next(),
+        MenuAction::Difficulty => *difficulty = difficulty.next(),
+        MenuAction::Resume => state.pop().unwrap(),
+        MenuAction::Finish => state.replace(GameState::TimeUp).unwrap(),
+        MenuAction::Restart => state.replace(GameState::Countdown).unwrap(),
+        // opened over the screen they came from, so a paused round stays underneath
+        MenuAction::Settings => state.push(GameState::Settings).unwrap(),
+        MenuAction::Leaderboard => state.push(GameState::Leaderboard).unwrap(),
+        MenuAction::Fullscreen => {
+            settings.fullscreen = !settings.fullscreen;
+            if let Some(window) = windows.get_primary_mut() {
+                window.set_mode(if settings.fullscreen {
+                    WindowMode::BorderlessFullscreen
+                } else {
+                    WindowMode::Windowed
+                });
+            }
+        }
+        MenuAction::Vsync => {
+            settings.vsync = !settings.vsync;
+            if let Some(window) = windows.get_primary_mut() {
+                window.set_vsync(settings.vsync);
+            }
+        }
+        MenuAction::Ghost => settings.ghost = !settings.ghost,
+        MenuAction::Back => state.pop().unwrap(),
+        MenuAction::MainMenu => state.replace(GameState::MainMenu).unwrap(),
+        MenuAction::Quit => exit.send(AppExit),
+    }
+}
+
+fn highlight_menu(
+    selection: Res<MenuSelection>,
+    materials: Res<MenuMaterials>,
+    settings: Res<Settings>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    mut items: Query<(&MenuItem, &mut Handle<ColorMaterial>, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (item, mut material, children) in items.iter_mut() {
+        *material = if item.index == selection.0 {
+            materials.selected.clone()
+        } else {
+            materials.normal.clone()
+        };
+        if settings.is_changed() || mode.is_changed() || difficulty.is_changed() {
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(*child) {
+                    text.sections[0].value = item.action.label(&settings, &mode, &difficulty);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// the rules of a round, picked from the main menu
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameMode {
+    // score as much as possible before the level's clock runs out
+    #[default]
+    Classic,
+    // no clock, every miss costs one of a few lives
+    Endless,
+    // no clock, the round ends with the last bullet
+    FixedAmmo,
+    // against the clock, the first miss ends the round
+    SuddenDeath,
+    // no clock, no misses, no high scores
+    Zen,
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Endless => "Endless",
+            GameMode::FixedAmmo => "Fixed Ammo",
+            GameMode::SuddenDeath => "Sudden Death",
+            GameMode::Zen => "Zen",
+        }
+    }
+
+    // names the mode's high score tables
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::Endless => "endless",
+            GameMode::FixedAmmo => "fixed_ammo",
+            GameMode::SuddenDeath => "sudden_death",
+            GameMode::Zen => "zen",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<GameMode> {
+        match key {
+            "classic" => Some(GameMode::Classic),
+            "endless" => Some(GameMode::Endless),
+            "fixed_ammo" => Some(GameMode::FixedAmmo),
+            "sudden_death" => Some(GameMode::SuddenDeath),
+            "zen" => Some(GameMode::Zen),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> GameMode {
+        match self {
+            GameMode::Classic => GameMode::Endless,
+            GameMode::Endless => GameMode::FixedAmmo,
+            GameMode::FixedAmmo => GameMode::SuddenDeath,
+            GameMode::SuddenDeath => GameMode::Zen,
+            GameMode::Zen => GameMode::Classic,
+        }
+    }
+
+    // whether the round runs against the level's clock, otherwise the clock counts up
+    pub fn timed(&self) -> bool {
+        matches!(self, GameMode::Classic | GameMode::SuddenDeath)
+    }
+
+    // misses allowed before the round ends
+    pub fn lives(&self) -> Option<usize> {
+        match self {
+            GameMode::Endless => Some(3),
+            GameMode::SuddenDeath => Some(1),
+            _ => None,
+        }
+    }
+
+    // bullets for the whole round, reloading only refills the magazine from them
+    pub fn shots(&self) -> Option<usize> {
+        match self {
+            GameMode::FixedAmmo => Some(20),
+            _ => None,
+        }
+    }
+
+    // whether rounds are won or lost and kept in the high scores
+    pub fn ranked(&self) -> bool {
+        *self != GameMode::Zen
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::f32::consts::TAU;
+
+// how a target moves, offsets and paths are relative to the target's spawn position
+#[derive(Clone, Debug, Deserialize)]
+pub enum MovementPattern {
+    // slide along the track at a constant speed, wrapping around at its ends
+    Linear {
+        speed: f32,
+    },
+    // slide like Linear while bobbing up and down
+    Sine {
+        speed: f32,
+        amplitude: f32,
+        period: f32,
+    },
+    // slide back and forth, reversing at min and max
+    Bounce {
+        speed: f32,
+        min: f32,
+        max: f32,
+    },
+    // follow straight lines through the points, looping back to the first one
+    Path {
+        points: Vec<(f32, f32)>,
+        duration: f32,
+    },
+    // follow a bezier curve through the control points and back again
+    Bezier {
+        points: Vec<(f32, f32)>,
+        duration: f32,
+    },
+    // stay hidden depth pixels below the spawn position, rise, stay up and sink again
+    PopUp {
+        depth: f32,
+        rise_time: f32,
+        up_time: f32,
+        down_time: f32,
+    },
+}
+
+impl MovementPattern {
+    // values the positions can't be worked out from, e.g. dividing by a zero duration
+    pub fn check(&self) -> anyhow::Result<()> {
+        match self {
+            MovementPattern::Sine { period, .. } if *period <= 0. => {
+                anyhow::bail!("Sine needs a period above 0")
+            }
+            MovementPattern::Bounce { min, max, .. } if min >= max => {
+                anyhow::bail!("Bounce needs a min below its max")
+            }
+            MovementPattern::Path { duration, .. } | MovementPattern::Bezier { duration, .. }
+                if *duration <= 0. =>
+            {
+                anyhow::bail!("Path and Bezier need a duration above 0")
+            }
+            MovementPattern::PopUp {
+                rise_time,
+                up_time,
+                down_time,
+                ..
+            } if *rise_time < 0. || *up_time < 0. || *down_time < 0. => {
+                anyhow::bail!("PopUp times can't be negative")
+            }
+            MovementPattern::PopUp {
+                rise_time,
+                up_time,
+                down_time,
+                ..
+            } if down_time + rise_time * 2. + up_time <= 0. => {
+                anyhow::bail!("PopUp needs some time to pass")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// stop moving for duration seconds after every seconds of movement
+#[derive(Clone, Debug, Deserialize)]
+pub struct Pause {
+    pub every: f32,
+    pub duration: f32,
+}
+
+impl Pause {
+    // moving_time divides by the length of a cycle
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.every <= 0. {
+            anyhow::bail!("a pause needs some movement before it");
+        }
+        if self.duration < 0. {
+            anyhow::bail!("a pause can't last a negative time");
+        }
+        Ok(())
+    }
+}
+
+pub struct Movement {
+    pub pattern: MovementPattern,
+    pub pause: Option<Pause>,
+    pub origin: Vec2,
+    pub track: (f32, f32),
+    pub elapsed: f32,
+    // how fast the pattern is played, 1 being as written in the level
+    pub speed: f32,
+}
+
+impl Movement {
+    pub fn position(&self) -> Vec2 {
+        let time = self.moving_time();
+        let origin = self.origin;
+        match &self.pattern {
+            MovementPattern::Linear { speed } => {
+                Vec2::new(wrap(origin.x + speed * time, self.track), origin.y)
+            }
+            MovementPattern::Sine {
+                speed,
+                amplitude,
+                period,
+            } => Vec2::new(
+                wrap(origin.x + speed * time, self.track),
+                origin.y + amplitude * (time / period * TAU).sin(),
+            ),
+            MovementPattern::Bounce { speed, min, max } => Vec2::new(
+                min + ping_pong(origin.x + speed * time - min, max - min),
+                origin.y,
+            ),
+            MovementPattern::Path { points, duration } => {
+                origin + along_path(points, (time / duration).fract())
+            }
+            MovementPattern::Bezier { points, duration } => {
+                origin + bezier(points, ping_pong(time / duration, 1.))
+            }
+            MovementPattern::PopUp {
+                depth,
+                rise_time,
+                up_time,
+                down_time,
+            } => {
+                let phase = time % (down_time + rise_time * 2. + up_time);
+                let hidden = if phase < *down_time {
+                    1.
+                } else if phase < down_time + rise_time {
+                    1. - (phase - down_time) / rise_time
+                } else if phase < down_time + rise_time + up_time {
+                    0.
+                } else {
+                    (phase - down_time - rise_time - up_time) / rise_time
+                };
+                origin - Vec2::new(0., depth * hidden)
+            }
+        }
+    }
+
+    // time spent moving so far, leaving out pauses
+    fn moving_time(&self) -> f32 {
+        match &self.pause {
+            Some(pause) if pause.every > 0. => {
+                let cycle = pause.every + pause.duration;
+                let laps = (self.elapsed / cycle).floor();
+                laps * pause.every + (self.elapsed - laps * cycle).min(pause.every)
+            }
+            _ => self.elapsed,
+        }
+    }
+}
+
+fn wrap(x: f32, (min, max): (f32, f32)) -> f32 {
+    min + (x - min).rem_euclid(max - min)
+}
+
+fn ping_pong(x: f32, length: f32) -> f32 {
+    let x = x.rem_euclid(length * 2.);
+    if x > length {
+        length * 2. - x
+    } else {
+        x
+    }
+}
+
+// point at a fraction of the way around a closed path, at constant speed
+fn along_path(points: &[(f32, f32)], fraction: f32) -> Vec2 {
+    let points: Vec<Vec2> = points.iter().map(|(x, y)| Vec2::new(*x, *y)).collect();
+    if points.len() < 2 {
+        return points.first().copied().unwrap_or(Vec2::ZERO);
+    }
+    let segments: Vec<(Vec2, Vec2)> = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(start, end)| (*start, *end))
+        .collect();
+    let length: f32 = segments
+        .iter()
+        .map(|(start, end)| start.distance(*end))
+        .sum();
+    let mut distance = fraction * length;
+    for (start, end) in segments.iter() {
+        let segment = start.distance(*end);
+        if distance <= segment && segment > 0. {
+            return start.lerp(*end, distance / segment);
+        }
+        distance -= segment;
+    }
+    points[0]
+}
+
+// de casteljau's algorithm, so any number of control points works
+fn bezier(points: &[(f32, f32)], t: f32) -> Vec2 {
+    let mut points: Vec<Vec2> = points.iter().map(|(x, y)| Vec2::new(*x, *y)).collect();
+    while points.len() > 1 {
+        points = points
+            .windows(2)
+            .map(|pair| pair[0].lerp(pair[1], t))
+            .collect();
+    }
+    points.first().copied().unwrap_or(Vec2::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movement(pattern: MovementPattern, elapsed: f32) -> Movement {
+        Movement {
+            pattern,
+            pause: None,
+            origin: Vec2::ZERO,
+            track: (-200., 200.),
+            elapsed,
+            speed: 1.,
+        }
+    }
+
+    fn assert_near(position: Vec2, expected: (f32, f32)) {
+        let expected = Vec2::new(expected.0, expected.1);
+        assert!(
+            position.distance(expected) < 1e-3,
+            "{} isn't {}",
+            position,
+            expected
+        );
+    }
+
+    #[test]
+    fn linear_wraps_around_the_track() {
+        let linear = MovementPattern::Linear { speed: 100. };
+        assert_near(movement(linear.clone(), 1.).position(), (100., 0.));
+        assert_near(movement(linear, 3.).position(), (-100., 0.));
+    }
+
+    #[test]
+    fn sine_bobs_by_its_amplitude() {
+        let sine = MovementPattern::Sine {
+            speed: 0.,
+            amplitude: 10.,
+            period: 4.,
+        };
+        assert_near(movement(sine.clone(), 1.).position(), (0., 10.));
+        assert_near(movement(sine, 3.).position(), (0., -10.));
+    }
+
+    #[test]
+    fn bounce_reverses_at_its_ends() {
+        let bounce = MovementPattern::Bounce {
+            speed: 50.,
+            min: -100.,
+            max: 100.,
+        };
+        assert_near(movement(bounce.clone(), 1.).position(), (50., 0.));
+        assert_near(movement(bounce.clone(), 3.).position(), (50., 0.));
+        assert_near(movement(bounce, 6.).position(), (-100., 0.));
+    }
+
+    #[test]
+    fn path_loops_back_to_its_first_point() {
+        let path = MovementPattern::Path {
+            points: vec![(0., 0.), (100., 0.), (100., 100.)],
+            duration: 4.,
+        };
+        // the closed path is about 341 long, the first corner a little over a quarter of the way
+        assert_near(movement(path.clone(), 0.).position(), (0., 0.));
+        assert_near(movement(path.clone(), 1.).position(), (85.355, 0.));
+        assert_near(movement(path, 4.).position(), (0., 0.));
+    }
+
+    #[test]
+    fn bezier_goes_there_and_back() {
+        let bezier = MovementPattern::Bezier {
+            points: vec![(0., 0.), (50., 100.), (100., 0.)],
+            duration: 2.,
+        };
+        assert_near(movement(bezier.clone(), 1.).position(), (50., 50.));
+        assert_near(movement(bezier.clone(), 2.).position(), (100., 0.));
+        assert_near(movement(bezier, 4.).position(), (0., 0.));
+    }
+
+    #[test]
+    fn pop_up_hides_rises_and_sinks() {
+        let pop_up = MovementPattern::PopUp {
+            depth: 50.,
+            rise_time: 1.,
+            up_time: 2.,
+            down_time: 1.,
+        };
+        assert_near(movement(pop_up.clone(), 0.5).position(), (0., -50.));
+        assert_near(movement(pop_up.clone(), 1.5).position(), (0., -25.));
+        assert_near(movement(pop_up.clone(), 3.).position(), (0., 0.));
+        assert_near(movement(pop_up, 4.5).position(), (0., -25.));
+    }
+
+    #[test]
+    fn pauses_need_movement_between_them() {
+        let pause = |every, duration| Pause { every, duration }.check().is_ok();
+        assert!(pause(1., 0.));
+        assert!(!pause(0., 1.));
+        assert!(!pause(1., -1.));
+    }
+
+    #[test]
+    fn pauses_hold_the_position() {
+        let mut linear = movement(MovementPattern::Linear { speed: 10. }, 1.5);
+        linear.pause = Some(Pause {
+            every: 1.,
+            duration: 1.,
+        });
+        assert_near(linear.position(), (10., 0.));
+        linear.elapsed = 2.5;
+        assert_near(linear.position(), (15., 0.));
+    }
+}
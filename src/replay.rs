@@ -0,0 +1,459 @@
+use crate::{
+    clock::SimulationClock, level::Level, mode::GameMode, step_set, waves::Difficulty, Game,
+    GameState, SCORE_LABEL, SIMULATION_STAGE,
+};
+use bevy::{
+    app::Events,
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ElementState},
+    prelude::*,
+    window::WindowId,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// bumped whenever the file layout or the way a seed plays out changes, older replays can't be
+// played back
+const VERSION: u32 = 4;
+const DIR: &str = "replays";
+const LAST: &str = "last.ron";
+// keys a round reacts to, escape only pauses and nothing moves on while paused
+const KEYS: [KeyCode; 1] = [KeyCode::R];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    Other(u16),
+}
+
+impl From<MouseButton> for Button {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => Button::Left,
+            MouseButton::Right => Button::Right,
+            MouseButton::Middle => Button::Middle,
+            MouseButton::Other(other) => Button::Other(other),
+        }
+    }
+}
+
+impl From<Button> for MouseButton {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::Left => MouseButton::Left,
+            Button::Right => MouseButton::Right,
+            Button::Middle => MouseButton::Middle,
+            Button::Other(other) => MouseButton::Other(other),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayInput {
+    // cursor position in the gallery, the centre being 0, 0, so it lands on the same spot whatever
+    // the size of the window played back in
+    Cursor(f32, f32),
+    Press(Button),
+    Release(Button),
+    Key(String),
+}
+
+// simulation steps of the round so far, the intro included and pauses left out,
+// recorded input is stamped with it
+#[derive(Default)]
+pub struct RoundSteps(pub u64);
+
+// the input of the round being played, saved as a replay once it's over
+#[derive(Default)]
+pub struct Recording {
+    // the input read on a frame, with the step it was read on
+    pub frames: Vec<(u64, Vec<ReplayInput>)>,
+    // the score whenever it changed, with the step it changed on
+    pub scores: Vec<(u64, usize)>,
+    // step the round stopped on
+    pub end: u64,
+}
+
+// everything it takes to play a round again: its rules, its seed and its input
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    pub level: String,
+    mode: String,
+    difficulty: String,
+    pub seed: u64,
+    // final score of the recorded round, the playback has to end on it too
+    pub score: usize,
+    pub end: u64,
+    // the input read on a frame, with the steps since the previous frame
+    frames: Vec<(u64, Vec<ReplayInput>)>,
+    // the score whenever it changed, with the steps since the previous change
+    scores: Vec<(u64, usize)>,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl Replay {
+    pub fn new(
+        recording: &Recording,
+        level: &Level,
+        mode: GameMode,
+        difficulty: Difficulty,
+        seed: u64,
+        score: usize,
+    ) -> Self {
+        Replay {
+            version: VERSION,
+            level: level.name.clone(),
+            mode: mode.key().to_string(),
+            difficulty: difficulty.key().to_string(),
+            seed,
+            score,
+            end: recording.end,
+            frames: waits(&recording.frames),
+            scores: waits(&recording.scores),
+        }
+    }
+
+    // checked when loaded
+    pub fn mode(&self) -> GameMode {
+        GameMode::from_key(&self.mode).unwrap_or_default()
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::from_key(&self.difficulty).unwrap_or_default()
+    }
+
+    // the input read on a frame, with the step it's read on
+    pub fn frames(&self) -> Vec<(u64, Vec<ReplayInput>)> {
+        steps(&self.frames)
+    }
+
+    // the score whenever it changed, with the step it changed on
+    pub fn scores(&self) -> Vec<(u64, usize)> {
+        steps(&self.scores)
+    }
+
+    // the best run so far of a level, mode and difficulty
+    pub fn best(level: &str, mode: GameMode, difficulty: Difficulty) -> Option<Replay> {
+        let path = path(&best_file(level, mode, difficulty)).filter(|path| path.exists())?;
+        match Replay::load(&path) {
+            Ok(replay) => Some(replay),
+            Err(error) => {
+                warn!("ignoring the best run in {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Replay> {
+        let bytes = fs::read(path)?;
+        let Version { version } = ron::de::from_bytes(&bytes)?;
+        if version != VERSION {
+            anyhow::bail!("unsupported version {}", version);
+        }
+        let replay: Replay = ron::de::from_bytes(&bytes)?;
+        if GameMode::from_key(&replay.mode).is_none() {
+            anyhow::bail!("unknown game mode {}", replay.mode);
+        }
+        if Difficulty::from_key(&replay.difficulty).is_none() {
+            anyhow::bail!("unknown difficulty {}", replay.difficulty);
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let text = ron::ser::to_string(self)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(fs::write(path, text)?)
+    }
+
+    // keep the round just played, replacing the one before
+    pub fn save_last(&self) {
+        self.save_as(LAST);
+    }
+
+    pub fn save_best(&self) {
+        self.save_as(&best_file(&self.level, self.mode(), self.difficulty()));
+    }
+
+    fn save_as(&self, file: &str) {
+        let path = match path(file) {
+            Some(path) => path,
+            None => return,
+        };
+        if let Err(error) = self.save(&path) {
+            warn!("couldn't save the replay to {}: {}", path.display(), error);
+        }
+    }
+}
+
+// stamp every item with the steps since the one before, instead of the step it's on
+fn waits<T: Clone>(items: &[(u64, T)]) -> Vec<(u64, T)> {
+    let mut previous = 0;
+    items
+        .iter()
+        .map(|(step, item)| {
+            let wait = step - previous;
+            previous = *step;
+            (wait, item.clone())
+        })
+        .collect()
+}
+
+fn steps<T: Clone>(items: &[(u64, T)]) -> Vec<(u64, T)> {
+    let mut step = 0;
+    items
+        .iter()
+        .map(|(wait, item)| {
+            step += wait;
+            (step, item.clone())
+        })
+        .collect()
+}
+
+fn best_file(level: &str, mode: GameMode, difficulty: Difficulty) -> String {
+    let level: String = level
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("best_{}_{}_{}.ron", level, mode.key(), difficulty.key())
+}
+
+fn path(file: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join(DIR).join(file))
+}
+
+// plays a replay's round from the main menu, its input standing in for the mouse and keyboard
+pub struct Playback {
+    replay: Replay,
+    frames: Vec<(u64, Vec<ReplayInput>)>,
+    next: usize,
+    started: bool,
+    finished: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            frames: replay.frames(),
+            replay,
+            next: 0,
+            started: false,
+            finished: false,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    // whether the replay is still being played, live input is ignored until it's over
+    pub fn active(&self) -> bool {
+        !self.finished
+    }
+}
+
+// records the input of every round, and plays rounds back from replays
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Countdown).with_system(start_recording.system()),
+        )
+        // read in the same states as the rifle reads them, so the same input gets recorded
+        .add_system_set(
+            SystemSet::on_update(GameState::Countdown).with_system(record_input.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(record_input.system())
+                .with_system(record_score.system().after(SCORE_LABEL)),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::TimeUp).with_system(stop_recording.system()))
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::Countdown).with_system(count_steps.system()),
+        )
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::Playing).with_system(count_steps.system()),
+        )
+        .add_system_to_stage(CoreStage::First, play_back.system());
+    }
+}
+
+fn start_recording(mut steps: ResMut<RoundSteps>, mut recording: ResMut<Recording>) {
+    steps.0 = 0;
+    *recording = Recording::default();
+}
+
+fn stop_recording(steps: Res<RoundSteps>, mut recording: ResMut<Recording>) {
+    recording.end = steps.0;
+}
+
+fn count_steps(mut steps: ResMut<RoundSteps>) {
+    steps.0 += 1;
+}
+
+fn record_input(
+    mut recording: ResMut<Recording>,
+    steps: Res<RoundSteps>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut mouse_button_input_events: EventReader<MouseButtonInput>,
+    keyboard_input: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+) {
+    // converted the way the rifle aims
+    let mut inputs: Vec<ReplayInput> = cursor_moved_events
+        .iter()
+        .filter_map(|event| {
+            let window = windows.get(event.id)?;
+            let position = event.position - Vec2::new(window.width(), window.height()) / 2.;
+            Some(ReplayInput::Cursor(position.x, position.y))
+        })
+        .collect();
+    inputs.extend(
+        mouse_button_input_events
+            .iter()
+            .map(|event| match event.state {
+                ElementState::Pressed => ReplayInput::Press(event.button.into()),
+                ElementState::Released => ReplayInput::Release(event.button.into()),
+            }),
+    );
+    inputs.extend(
+        KEYS.iter()
+            .filter(|key| keyboard_input.just_pressed(**key))
+            .map(|key| ReplayInput::Key(format!("{:?}", key))),
+    );
+    if !inputs.is_empty() {
+        recording.frames.push((steps.0, inputs));
+    }
+}
+
+fn record_score(mut recording: ResMut<Recording>, steps: Res<RoundSteps>, game: Res<Game>) {
+    let last = recording.scores.last().map_or(0, |(_, score)| *score);
+    if game.score != last {
+        recording.scores.push((steps.0, game.score));
+    }
+}
+
+// start the replay's round from the main menu, then on every frame either feed it the input read
+// on the current step or move on by one step, so the input lands on the steps it was recorded on
+fn play_back(
+    playback: Option<ResMut<Playback>>,
+    mut clock: ResMut<SimulationClock>,
+    mut state: ResMut<State<GameState>>,
+    steps: Res<RoundSteps>,
+    game: Res<Game>,
+    level: Option<Res<Level>>,
+    windows: Res<Windows>,
+    mut cursor_moved_events: ResMut<Events<CursorMoved>>,
+    mut mouse_button_input_events: ResMut<Events<MouseButtonInput>>,
+    mut keyboard_input_events: ResMut<Events<KeyboardInput>>,
+) {
+    let mut playback = match playback {
+        Some(playback) if playback.active() => playback,
+        _ => return,
+    };
+    cursor_moved_events.clear();
+    mouse_button_input_events.clear();
+    keyboard_input_events.clear();
+
+    match state.current() {
+        GameState::MainMenu if !playback.started => {
+            let level = match level {
+                Some(level) => level,
+                None => return,
+            };
+            if level.name != playback.replay.level {
+                warn!(
+                    "the replay is of level {}, not {}",
+                    playback.replay.level, level.name
+                );
+                playback.finished = true;
+                return;
+            }
+            clock.set_manual(true);
+            playback.started = state.set(GameState::Countdown).is_ok();
+        }
+        GameState::Countdown | GameState::Playing => {
+            let next = playback.next;
+            match playback.frames.get(next) {
+                Some((step, inputs)) if *step <= steps.0 => {
+                    // back to the coordinates of the window played back in
+                    let window = windows.get_primary().map_or(Vec2::ZERO, |window| {
+                        Vec2::new(window.width(), window.height()) / 2.
+                    });
+                    for input in inputs.iter() {
+                        match input {
+                            ReplayInput::Cursor(x, y) => cursor_moved_events.send(CursorMoved {
+                                id: WindowId::primary(),
+                                position: Vec2::new(*x, *y) + window,
+                            }),
+                            ReplayInput::Press(button) => {
+                                mouse_button_input_events.send(MouseButtonInput {
+                                    button: (*button).into(),
+                                    state: ElementState::Pressed,
+                                })
+                            }
+                            ReplayInput::Release(button) => {
+                                mouse_button_input_events.send(MouseButtonInput {
+                                    button: (*button).into(),
+                                    state: ElementState::Released,
+                                })
+                            }
+                            ReplayInput::Key(name) => {
+                                let key = KEYS.iter().find(|key| format!("{:?}", key) == *name);
+                                for state in [ElementState::Pressed, ElementState::Released].iter()
+                                {
+                                    keyboard_input_events.send(KeyboardInput {
+                                        scan_code: 0,
+                                        key_code: key.copied(),
+                                        state: *state,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                    playback.next += 1;
+                }
+                // the recorded round was finished from the pause menu
+                _ if *state.current() == GameState::Playing && steps.0 >= playback.replay.end => {
+                    let _ = state.set(GameState::TimeUp);
+                }
+                _ => clock.advance(1),
+            }
+        }
+        GameState::TimeUp => clock.advance(1),
+        GameState::GameOver if playback.started => {
+            playback.finished = true;
+            clock.set_manual(false);
+            if game.score == playback.replay.score {
+                info!("replay ended on its recorded score of {}", game.score);
+            } else {
+                warn!(
+                    "replay ended on a score of {} instead of the recorded {}",
+                    game.score, playback.replay.score
+                );
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    level::Level,
+    mode::GameMode,
+    replay::{Playback, Recording, Replay},
+    rng::GameRng,
+    scores::{HighScore, HighScores},
+    targets::WaveDirector,
+    waves::Difficulty,
+    Game, GameState, Stats,
+};
+use bevy::prelude::*;
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// arcade style initials typed in on the results screen after a high score
+pub struct NameEntry {
+    pub letters: [u8; 3],
+    pub cursor: usize,
+}
+pub struct NameEntryText;
+
+// the outcome of a round, and the entry of a new high score
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<HighScores>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(display_score.system())
+                    .with_system(save_replay.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(gameover_keyboard.system())
+                    .with_system(enter_initials.system()),
+            );
+    }
+}
+
+// restart the game when pressing spacebar, or go back to the menu with escape
+fn gameover_keyboard(
+    mut state: ResMut<State<GameState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    entry: Option<Res<NameEntry>>,
+) {
+    if entry.is_some() {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        state.set(GameState::Countdown).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::MainMenu).unwrap();
+    }
+}
+
+// show the outcome, grade and statistics of the round
+fn display_score(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game: Res<Game>,
+    stats: Res<Stats>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    director: Option<Res<WaveDirector>>,
+    rng: Res<GameRng>,
+    high_scores: Res<HighScores>,
+    playback: Option<Res<Playback>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let section = |value: String, font_size: f32, color: Color| TextSection {
+        value,
+        style: TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        },
+    };
+
+    let grade = level.grade(game.score).filter(|_| mode.ranked());
+    // a level without grades can't be lost
+    let won = grade.is_some() || level.grades.is_empty();
+    let mut sections = vec![if !mode.ranked() {
+        section("Round Over\n".to_string(), 60., Color::WHITE)
+    } else if won {
+        section("You Won!\n".to_string(), 60., Color::rgb(0.0, 1.0, 0.0))
+    } else {
+        section("You Lost\n".to_string(), 60., Color::rgb(1.0, 0.2, 0.2))
+    }];
+    // the waves are only directed along with the stock targets
+    let rules = match director.filter(|_| level.waves.is_some()) {
+        Some(director) => format!(
+            "{} - {} - {} - Wave {} - Seed {}\n",
+            level.name,
+            mode.name(),
+            difficulty.name(),
+            director.wave,
+            rng.seed()
+        ),
+        None => format!("{} - {}\n", level.name, mode.name()),
+    };
+    sections.push(section(rules, 28., Color::rgb(0.8, 0.8, 0.8)));
+    sections.push(section(
+        format!("Final Score: {}\n", game.score),
+        40.,
+        Color::WHITE,
+    ));
+    if let Some(grade) = grade {
+        sections.push(section(
+            format!("Grade: {}\n", grade.name),
+            40.,
+            Color::rgb(1.0, 0.8, 0.2),
+        ));
+    }
+    sections.push(section(
+        format!(
+            "\nShots: {}   Hits: {}   Accuracy: {:.0}%   Best Streak: {}   Best Multiplier: x{}\n",
+            stats.shots,
+            stats.hits,
+            stats.accuracy(),
+            stats.best_streak,
+            stats.best_multiplier.max(1)
+        ),
+        28.,
+        Color::WHITE,
+    ));
+    for (target, hits) in stats.hits_by_target.iter() {
+        sections.push(section(
+            format!(
+                "{}: {} hit{}\n",
+                target,
+                hits,
+                if *hits == 1 { "" } else { "s" }
+            ),
+            24.,
+            Color::rgb(0.8, 0.8, 0.8),
+        ));
+    }
+    if stats.penalties > 0 {
+        sections.push(section(
+            format!("Penalties: {}\n", stats.penalties),
+            24.,
+            Color::rgb(1.0, 0.4, 0.4),
+        ));
+    }
+    for (zone, points) in stats.points_by_zone.iter() {
+        sections.push(section(
+            format!("{}: {} points\n", zone, points),
+            24.,
+            Color::rgb(0.8, 0.8, 0.8),
+        ));
+    }
+    sections.push(section(
+        "\nPress Spacebar to Play Again or Escape for the Menu".to_string(),
+        32.,
+        Color::rgb(0.0, 1.0, 0.0),
+    ));
+
+    // a replay played back isn't a new score
+    let played_back = playback.is_some_and(|playback| playback.active());
+    let high_score = mode.ranked()
+        && !played_back
+        && high_scores.qualifies(&level.name, mode.key(), difficulty.key(), game.score);
+    if high_score {
+        commands.insert_resource(NameEntry {
+            letters: *b"AAA",
+            cursor: 0,
+        });
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                margin: bevy::math::Rect::all(Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text {
+                    sections,
+                    alignment: TextAlignment {
+                        horizontal: HorizontalAlign::Center,
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            });
+            if high_score {
+                parent
+                    .spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            "",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 32.,
+                                color: Color::rgb(1.0, 0.8, 0.2),
+                            },
+                            TextAlignment {
+                                horizontal: HorizontalAlign::Center,
+                                ..Default::default()
+                            },
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(NameEntryText);
+            }
+        });
+}
+
+// keep the input of the round that just ended, to play it back later or race it as a ghost
+fn save_replay(
+    recording: Res<Recording>,
+    game: Res<Game>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    rng: Res<GameRng>,
+    playback: Option<Res<Playback>>,
+) {
+    if playback.is_some_and(|playback| playback.active()) {
+        return;
+    }
+    let replay = Replay::new(
+        &recording,
+        &level,
+        *mode,
+        *difficulty,
+        rng.seed(),
+        game.score,
+    );
+    replay.save_last();
+    // the ghost of later rounds
+    if game.score > 0
+        && Replay::best(&level.name, *mode, *difficulty).is_none_or(|best| game.score > best.score)
+    {
+        replay.save_best();
+    }
+}
+
+// type initials or pick them with the arrow keys, enter saves them and escape skips the entry
+fn enter_initials(
+    mut commands: Commands,
+    entry: Option<ResMut<NameEntry>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut high_scores: ResMut<HighScores>,
+    game: Res<Game>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    mut texts: Query<&mut Text, With<NameEntryText>>,
+) {
+    let mut entry = match entry {
+        Some(entry) => entry,
+        None => return,
+    };
+    let cursor = entry.cursor;
+    let letter = ALPHABET
+        .iter()
+        .position(|letter| *letter == entry.letters[cursor])
+        .unwrap_or(0);
+    for event in characters.iter() {
+        let typed = event.char.to_ascii_uppercase() as u8;
+        if ALPHABET.contains(&typed) {
+            let typed_at = entry.cursor;
+            entry.letters[typed_at] = typed;
+            entry.cursor = (typed_at + 1).min(entry.letters.len() - 1);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        entry.letters[cursor] = ALPHABET[(letter + 1) % ALPHABET.len()];
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        entry.letters[cursor] = ALPHABET[(letter + ALPHABET.len() - 1) % ALPHABET.len()];
+    }
+    if keyboard_input.just_pressed(KeyCode::Left) || keyboard_input.just_pressed(KeyCode::Back) {
+        entry.cursor = entry.cursor.saturating_sub(1);
+    }
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        entry.cursor = (entry.cursor + 1).min(entry.letters.len() - 1);
+    }
+
+    let text = if keyboard_input.just_pressed(KeyCode::Return) {
+        let initials = String::from_utf8_lossy(&entry.letters).to_string();
+        let rank = high_scores.insert(
+            &level.name,
+            mode.key(),
+            difficulty.key(),
+            HighScore {
+                initials: initials.clone(),
+                score: game.score,
+            },
+        );
+        high_scores.save();
+        commands.remove_resource::<NameEntry>();
+        format!(
+            "{} is number {} on the high score table!",
+            initials,
+            rank + 1
+        )
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<NameEntry>();
+        String::new()
+    } else {
+        let letters: Vec<String> = entry
+            .letters
+            .iter()
+            .enumerate()
+            .map(|(index, letter)| {
+                if index == entry.cursor {
+                    format!("[{}]", *letter as char)
+                } else {
+                    format!(" {} ", *letter as char)
+                }
+            })
+            .collect();
+        format!(
+            "New High Score! Enter your initials: {}\nType or use the arrow keys, Enter to save",
+            letters.join("")
+        )
+    };
+    for mut section in texts.iter_mut() {
+        section.sections[0].value = text.clone();
+    }
+}
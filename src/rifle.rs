@@ -0,0 +1,248 @@
+use crate::{
+    clock::SimulationClock, level::Level, mode::GameMode, step_set, AssetsReloaded, Game,
+    GameState, Textures, FIRE_LABEL, HOT_RELOAD_LABEL, SIMULATION_STAGE,
+};
+use bevy::{
+    app::Events,
+    input::{mouse::MouseButtonInput, ElementState},
+    prelude::*,
+};
+
+pub struct Crosshair;
+pub struct Rifle;
+// how the rifle is held and handled, insert it before adding the plugins to change it
+pub struct RifleConfig {
+    // where the rifle sits from the crosshair, right and down
+    pub crosshair_offset: Vec2,
+    pub reload_time: f32,
+}
+impl Default for RifleConfig {
+    fn default() -> Self {
+        RifleConfig {
+            crosshair_offset: Vec2::new(100., 200.),
+            reload_time: 1.2,
+        }
+    }
+}
+// fired at a position in the gallery, the centre being 0, 0
+pub struct Shot {
+    pub position: Vec2,
+}
+
+// the crosshair following the mouse, firing, dry firing and reloading the rifle
+pub struct RiflePlugin;
+
+impl Plugin for RiflePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<RifleConfig>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Countdown).with_system(setup_rifle.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Countdown).with_system(mouse_move_events.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing).with_system(capture_input.system()),
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::Playing).with_system(capture_input.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(mouse_move_events.system().before(FIRE_LABEL))
+                    .with_system(mouse_button_events.system().label(FIRE_LABEL))
+                    .with_system(reload_keyboard.system())
+                    .with_system(respawn_rifle.system().after(HOT_RELOAD_LABEL)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::TimeUp).with_system(rest_rifle.system()))
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                step_set(GameState::Playing).with_system(reload.system()),
+            );
+    }
+}
+
+fn setup_rifle(mut commands: Commands, texture: Res<Textures>, config: Res<RifleConfig>) {
+    spawn_rifle(&mut commands, &texture, &config);
+}
+
+// put the rifle back after the gallery was rebuilt from changed assets
+fn respawn_rifle(
+    mut commands: Commands,
+    mut events: EventReader<AssetsReloaded>,
+    texture: Res<Textures>,
+    config: Res<RifleConfig>,
+) {
+    if events.iter().count() > 0 {
+        spawn_rifle(&mut commands, &texture, &config);
+    }
+}
+
+fn spawn_rifle(commands: &mut Commands, texture: &Textures, config: &RifleConfig) {
+    // setup rifle
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: texture.sprites_objects.atlas.clone(),
+            transform: Transform::from_xyz(
+                config.crosshair_offset.x,
+                -config.crosshair_offset.y,
+                4.,
+            ),
+            sprite: TextureAtlasSprite {
+                index: texture.sprites_objects.index("rifle.png"),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Rifle);
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: texture.sprites_hud.atlas.clone(),
+            transform: Transform::from_xyz(0., 0., 4.),
+            sprite: TextureAtlasSprite {
+                index: texture.sprites_hud.index("crosshair_white_large.png"),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Crosshair);
+}
+
+// hide the cursor behind the crosshair, and drop the click that started or resumed the round
+fn capture_input(
+    mut windows: ResMut<Windows>,
+    mut mouse_button_input_events: ResMut<Events<MouseButtonInput>>,
+    mut mouse_button_input: ResMut<Input<MouseButton>>,
+) {
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_cursor_visibility(false);
+    }
+    mouse_button_input_events.clear();
+    mouse_button_input.reset(MouseButton::Left);
+}
+
+fn mouse_move_events(
+    mut transforms: QuerySet<(
+        Query<&mut Transform, With<Rifle>>,
+        Query<&mut Transform, With<Crosshair>>,
+    )>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut game: ResMut<Game>,
+    windows: Res<Windows>,
+    config: Res<RifleConfig>,
+) {
+    for event in cursor_moved_events.iter() {
+        let window = match windows.get(event.id) {
+            Some(window) => window,
+            None => continue,
+        };
+        let new_x = event.position.x - window.width() / 2.;
+        let new_y = event.position.y - window.height() / 2.;
+        for mut transform in transforms.q0_mut().iter_mut() {
+            transform.translation.x = new_x + config.crosshair_offset.x;
+            transform.translation.y = new_y - config.crosshair_offset.y;
+        }
+        for mut transform in transforms.q1_mut().iter_mut() {
+            transform.translation.x = new_x;
+            transform.translation.y = new_y;
+            game.last_mouse = Vec2::new(new_x, new_y);
+        }
+    }
+}
+
+fn mouse_button_events(
+    mut transforms: QuerySet<(
+        Query<&mut TextureAtlasSprite, With<Rifle>>,
+        Query<&mut TextureAtlasSprite, With<Crosshair>>,
+    )>,
+    mut mouse_button_input_events: EventReader<MouseButtonInput>,
+    mut shots: EventWriter<Shot>,
+    mut game: ResMut<Game>,
+    texture: Res<Textures>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+    config: Res<RifleConfig>,
+) {
+    for event in mouse_button_input_events.iter() {
+        // the round is waiting for the outro to start
+        if game.round_over(&mode) {
+            continue;
+        }
+        if event.button == MouseButton::Left && event.state == ElementState::Pressed {
+            // dry fire while the magazine is empty or being reloaded
+            if game.ammo == 0 || game.reload.is_some() {
+                for mut sprite in transforms.q1_mut().iter_mut() {
+                    sprite.index = texture.sprites_hud.index("crosshair_outline_large.png");
+                }
+                continue;
+            }
+            game.ammo -= 1;
+            if let Some(shots) = game.shots_left.as_mut() {
+                *shots = shots.saturating_sub(1);
+            }
+            shots.send(Shot {
+                position: game.last_mouse,
+            });
+            for mut sprite in transforms.q0_mut().iter_mut() {
+                sprite.index = texture.sprites_objects.index("rifle_red.png");
+            }
+            for mut sprite in transforms.q1_mut().iter_mut() {
+                sprite.index = texture.sprites_hud.index("crosshair_red_large.png");
+            }
+        } else if event.button == MouseButton::Right && event.state == ElementState::Pressed {
+            start_reload(&mut game, &level, &config);
+        } else {
+            for mut sprite in transforms.q0_mut().iter_mut() {
+                sprite.index = texture.sprites_objects.index("rifle.png");
+            }
+            for mut sprite in transforms.q1_mut().iter_mut() {
+                sprite.index = texture.sprites_hud.index("crosshair_white_large.png");
+            }
+        }
+    }
+}
+
+// the button release that would reset the sprites is no longer read once the round is over
+fn rest_rifle(
+    texture: Res<Textures>,
+    mut sprites: QuerySet<(
+        Query<&mut TextureAtlasSprite, With<Rifle>>,
+        Query<&mut TextureAtlasSprite, With<Crosshair>>,
+    )>,
+) {
+    for mut sprite in sprites.q0_mut().iter_mut() {
+        sprite.index = texture.sprites_objects.index("rifle.png");
+    }
+    for mut sprite in sprites.q1_mut().iter_mut() {
+        sprite.index = texture.sprites_hud.index("crosshair_white_large.png");
+    }
+}
+
+pub fn start_reload(game: &mut Game, level: &Level, config: &RifleConfig) {
+    if game.reload.is_none() && game.ammo < game.magazine(level) {
+        game.reload = Some(Timer::from_seconds(config.reload_time, false));
+    }
+}
+
+fn reload_keyboard(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    level: Res<Level>,
+    config: Res<RifleConfig>,
+) {
+    if keyboard_input.just_pressed(KeyCode::R) {
+        start_reload(&mut game, &level, &config);
+    }
+}
+
+// refill the magazine once the reload delay has passed
+fn reload(clock: Res<SimulationClock>, mut game: ResMut<Game>, level: Res<Level>) {
+    let reloaded = match game.reload.as_mut() {
+        Some(timer) => timer.tick(clock.delta()).finished(),
+        None => false,
+    };
+    if reloaded {
+        game.ammo = game.magazine(&level);
+        game.reload = None;
+    }
+}
@@ -0,0 +1,72 @@
+use crate::level::Level;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// the only source of randomness of a round, reseeded when it starts so the same seed and the same
+// input play out the same round; systems drawing from it must be ordered against each other
+pub struct GameRng {
+    // given on the command line, wins over the level's seed and the day's
+    pub fixed_seed: Option<u64>,
+    seed: u64,
+    // named instead of rand's StdRng, whose algorithm may change between releases and with it
+    // every round a saved seed or replay stands for
+    rng: ChaCha8Rng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng {
+            fixed_seed: None,
+            seed: 0,
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+}
+
+impl GameRng {
+    pub fn with_seed(seed: u64) -> Self {
+        GameRng {
+            fixed_seed: Some(seed),
+            ..Default::default()
+        }
+    }
+
+    // seed of the current round
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // start over for a new round
+    pub fn reseed(&mut self, level: &Level) {
+        self.seed = self.fixed_seed.or(level.seed).unwrap_or_else(daily_seed);
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+// days since the unix epoch, so everyone gets the same rounds on the same day
+pub fn daily_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / SECONDS_PER_DAY)
+}
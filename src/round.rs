@@ -0,0 +1,181 @@
+use crate::{
+    clock::{self, SimulationClock},
+    level::Level,
+    mode::GameMode,
+    step_set, Game, GameState, Stats, Textures, SIMULATION_STAGE,
+};
+use bevy::prelude::*;
+
+const READY_TIME: f32 = 1.5;
+const GO_TIME: f32 = 0.75;
+const OUTRO_TIME: f32 = 2.;
+const COUNT_DOWN_LABEL: &str = "count_down";
+
+// "ready", "go" or "time up" across the middle of the screen
+pub struct Banner;
+// timing of the round intro and outro
+pub struct Sequence {
+    pub timer: Timer,
+}
+
+// the course of a round: the intro, the clock, pausing, the end and the outro
+pub struct RoundPlugin;
+
+impl Plugin for RoundPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Countdown)
+                .with_system(start_round.system())
+                .with_system(start_intro.system()),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Countdown).with_system(despawn_banner.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing).with_system(pause_keyboard.system()),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::TimeUp).with_system(start_outro.system()))
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::Countdown).with_system(intro.system()),
+        )
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::Playing)
+                .with_system(count_down.system().label(COUNT_DOWN_LABEL))
+                .with_system(end_round.system().after(COUNT_DOWN_LABEL)),
+        )
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::TimeUp).with_system(outro.system()),
+        );
+    }
+}
+
+pub fn start_round(
+    mut game: ResMut<Game>,
+    mut stats: ResMut<Stats>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+) {
+    game.score = 0;
+    game.time_left = level.round_time;
+    game.ticks = 0;
+    game.elapsed = 0;
+    game.lives = mode.lives();
+    game.shots_left = mode.shots();
+    game.ammo = game.magazine(&level);
+    game.reload = None;
+    game.break_combo();
+    *stats = Stats::default();
+}
+
+// end the round once the rules of the mode say it's over
+fn end_round(
+    mut state: ResMut<State<GameState>>,
+    mut clock: ResMut<SimulationClock>,
+    mode: Res<GameMode>,
+    game: Res<Game>,
+) {
+    if game.round_over(&mode) {
+        // tried again next step if another transition is already queued
+        let _ = state.set(GameState::TimeUp);
+        clock.hold();
+    }
+}
+
+// show "ready" then "go" over the frozen gallery before the clock starts
+fn start_intro(mut commands: Commands, texture: Res<Textures>) {
+    spawn_banner(&mut commands, &texture, "text_ready.png");
+    commands.insert_resource(Sequence {
+        timer: Timer::from_seconds(READY_TIME + GO_TIME, false),
+    });
+}
+
+fn intro(
+    mut clock: ResMut<SimulationClock>,
+    mut state: ResMut<State<GameState>>,
+    mut sequence: ResMut<Sequence>,
+    mut banners: Query<&mut TextureAtlasSprite, With<Banner>>,
+    texture: Res<Textures>,
+) {
+    sequence.timer.tick(clock.delta());
+    if sequence.timer.elapsed_secs() >= READY_TIME {
+        for mut sprite in banners.iter_mut() {
+            sprite.index = texture.sprites_hud.index("text_go.png");
+        }
+    }
+    if sequence.timer.just_finished() {
+        state.set(GameState::Playing).unwrap();
+        clock.hold();
+    }
+}
+
+// show "time up" while knocked down targets and bullet holes settle, then move on to the results
+fn start_outro(
+    mut commands: Commands,
+    texture: Res<Textures>,
+    game: Res<Game>,
+    mode: Res<GameMode>,
+) {
+    let banner = if mode.timed() && game.time_left == 0 {
+        "text_timeup.png"
+    } else {
+        "text_gameover.png"
+    };
+    spawn_banner(&mut commands, &texture, banner);
+    commands.insert_resource(Sequence {
+        timer: Timer::from_seconds(OUTRO_TIME, false),
+    });
+}
+
+fn outro(
+    mut clock: ResMut<SimulationClock>,
+    mut state: ResMut<State<GameState>>,
+    mut sequence: ResMut<Sequence>,
+) {
+    if sequence.timer.tick(clock.delta()).just_finished() {
+        state.set(GameState::GameOver).unwrap();
+        clock.hold();
+    }
+}
+
+fn spawn_banner(commands: &mut Commands, texture: &Textures, sprite: &str) {
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: texture.sprites_hud.atlas.clone(),
+            transform: Transform::from_xyz(0., 0., 5.),
+            sprite: TextureAtlasSprite {
+                index: texture.sprites_hud.index(sprite),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Banner);
+}
+
+fn despawn_banner(mut commands: Commands, banners: Query<Entity, With<Banner>>) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// pause the round with escape, freezing everything that runs while playing
+fn pause_keyboard(mut state: ResMut<State<GameState>>, mut keyboard_input: ResMut<Input<KeyCode>>) {
+    // the round may be ending on this frame
+    if keyboard_input.just_pressed(KeyCode::Escape) && state.push(GameState::Paused).is_ok() {
+        keyboard_input.reset(KeyCode::Escape);
+    }
+}
+
+fn count_down(mode: Res<GameMode>, mut game: ResMut<Game>) {
+    game.ticks += 1;
+    if game.ticks % clock::STEPS_PER_SECOND != 0 {
+        return;
+    }
+
+    game.elapsed += 1;
+    if mode.timed() {
+        game.time_left = game.time_left.saturating_sub(1);
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+// bumped whenever the file layout changes, older files are discarded
+const VERSION: u32 = 2;
+const FILE: &str = "highscores.ron";
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: usize,
+}
+
+// a table of scores for every mode and difficulty
+type Tables = BTreeMap<String, BTreeMap<String, Vec<HighScore>>>;
+
+// the best scores of every level, game mode and difficulty, kept in the platform data directory
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HighScores {
+    version: u32,
+    levels: BTreeMap<String, Tables>,
+}
+
+// read from disk when the plugins set the resource up, so embedding apps don't start from an empty table
+// that the next saved score would write over
+impl FromWorld for HighScores {
+    fn from_world(_world: &mut World) -> Self {
+        HighScores::load()
+    }
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+impl HighScores {
+    fn empty() -> Self {
+        HighScores {
+            version: VERSION,
+            levels: BTreeMap::new(),
+        }
+    }
+
+    pub fn load() -> Self {
+        let path = match path() {
+            Some(path) if path.exists() => path,
+            _ => return HighScores::empty(),
+        };
+        let loaded = fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| {
+                let Version { version } = ron::de::from_bytes(&bytes)?;
+                if version != VERSION {
+                    anyhow::bail!("unsupported version {}", version);
+                }
+                Ok(ron::de::from_bytes(&bytes)?)
+            });
+        match loaded {
+            Ok(scores) => scores,
+            Err(error) => {
+                warn!("ignoring high scores in {}: {}", path.display(), error);
+                HighScores::empty()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let path = match path() {
+            Some(path) => path,
+            None => return,
+        };
+        let saved = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(anyhow::Error::from)
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                Ok(fs::write(&path, text)?)
+            });
+        if let Err(error) = saved {
+            warn!("couldn't save high scores to {}: {}", path.display(), error);
+        }
+    }
+
+    pub fn table(&self, level: &str, mode: &str, difficulty: &str) -> &[HighScore] {
+        self.levels
+            .get(level)
+            .and_then(|modes| modes.get(mode))
+            .and_then(|difficulties| difficulties.get(difficulty))
+            .map_or(&[], |table| table.as_slice())
+    }
+
+    pub fn qualifies(&self, level: &str, mode: &str, difficulty: &str, score: usize) -> bool {
+        let table = self.table(level, mode, difficulty);
+        score > 0 && (table.len() < MAX_ENTRIES || table.iter().any(|entry| score > entry.score))
+    }
+
+    // add a score below any equal ones, returning its rank from 0
+    pub fn insert(&mut self, level: &str, mode: &str, difficulty: &str, entry: HighScore) -> usize {
+        let table = self
+            .levels
+            .entry(level.to_string())
+            .or_default()
+            .entry(mode.to_string())
+            .or_default()
+            .entry(difficulty.to_string())
+            .or_default();
+        let rank = table
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(table.len());
+        table.insert(rank, entry);
+        table.truncate(MAX_ENTRIES);
+        rank
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join(FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, score: usize) -> HighScore {
+        HighScore {
+            initials: initials.to_string(),
+            score,
+        }
+    }
+
+    #[test]
+    fn ties_rank_below_earlier_scores() {
+        let mut scores = HighScores::empty();
+        assert_eq!(
+            scores.insert("gallery", "classic", "normal", entry("AAA", 100)),
+            0
+        );
+        assert_eq!(
+            scores.insert("gallery", "classic", "normal", entry("BBB", 200)),
+            0
+        );
+        assert_eq!(
+            scores.insert("gallery", "classic", "normal", entry("CCC", 100)),
+            2
+        );
+        let initials: Vec<&str> = scores
+            .table("gallery", "classic", "normal")
+            .iter()
+            .map(|entry| entry.initials.as_str())
+            .collect();
+        assert_eq!(initials, ["BBB", "AAA", "CCC"]);
+        assert!(scores.table("gallery", "classic", "hard").is_empty());
+        assert!(scores.table("gallery", "zen", "normal").is_empty());
+    }
+
+    #[test]
+    fn full_tables_keep_the_best_ten() {
+        let mut scores = HighScores::empty();
+        assert!(!scores.qualifies("gallery", "classic", "normal", 0));
+        for score in 1..=MAX_ENTRIES {
+            assert!(scores.qualifies("gallery", "classic", "normal", score * 10));
+            scores.insert("gallery", "classic", "normal", entry("AAA", score * 10));
+        }
+        assert!(!scores.qualifies("gallery", "classic", "normal", 10));
+        assert!(scores.qualifies("gallery", "classic", "normal", 11));
+        assert!(scores.qualifies("gallery", "classic", "hard", 10));
+
+        assert_eq!(
+            scores.insert("gallery", "classic", "normal", entry("BBB", 15)),
+            9
+        );
+        let table = scores.table("gallery", "classic", "normal");
+        assert_eq!(table.len(), MAX_ENTRIES);
+        assert_eq!(table.last().unwrap().score, 15);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{
+    clock::{Motion, SimulationClock},
+    digits::{self, Align, DigitDisplay, DigitFormat, Padding},
+    hit::{hit_zone, AlphaMasks, Zone},
+    level::{Level, TargetKind},
+    rifle::Shot,
+    stall::Cover,
+    step_set,
+    targets::{KnockedDown, Target, TargetFace},
+    ComboConfig, Game, GameState, Stats, Textures, FIRE_LABEL, RESOLVE_LABEL, SCORE_LABEL,
+    SIMULATION_STAGE,
+};
+use bevy::prelude::*;
+
+const DECAL_TARGET: &str = "shot_grey_small.png";
+const DECAL_BACKDROP: &str = "shot_brown_large.png";
+const DECAL_BACKDROP_Z: f32 = 1.705;
+const DECAL_TIME: f32 = 8.;
+const DECAL_FADE_TIME: f32 = 1.;
+const POPUP_TIME: f32 = 0.8;
+const POPUP_SPEED: f32 = 80.;
+// where pixel perfect hits outside every zone of a target are counted
+const EDGE_ZONE: &str = "edge";
+
+// bullet holes kept in the gallery at once, the oldest going first past the cap
+pub struct DecalConfig {
+    pub max_decals: usize,
+}
+impl Default for DecalConfig {
+    fn default() -> Self {
+        DecalConfig { max_decals: 24 }
+    }
+}
+
+// points won, floating up from where the shot landed
+pub struct Popup {
+    pub timer: Timer,
+}
+pub struct Decal {
+    pub timer: Timer,
+}
+// what a shot did, for score_shots to apply the rules of
+pub enum ShotOutcome {
+    Hit {
+        position: Vec2,
+        target: String,
+        kind: TargetKind,
+        zone: String,
+        // the target's points times the multiplier of the zone that was hit
+        points: usize,
+    },
+    Miss,
+}
+// what a shot hit first
+enum Impact<'a> {
+    Target {
+        entity: Entity,
+        target: &'a Target,
+        zone: Option<&'a Zone>,
+    },
+    Cover(Option<u32>),
+}
+
+// what every shot hits, the bullet holes it leaves, and the points, combo and popups that come of it
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<DecalConfig>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(
+                        resolve_shots
+                            .system()
+                            .label(RESOLVE_LABEL)
+                            .after(FIRE_LABEL),
+                    )
+                    .with_system(score_shots.system().label(SCORE_LABEL).after(RESOLVE_LABEL)),
+            )
+            // textures change on disk whatever screen is up
+            .add_system(build_alpha_masks.system())
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                step_set(GameState::Playing)
+                    .with_system(decay_combo.system())
+                    .with_system(fade_decals.system())
+                    .with_system(float_popups.system()),
+            )
+            .add_system_set_to_stage(
+                SIMULATION_STAGE,
+                step_set(GameState::TimeUp)
+                    .with_system(fade_decals.system())
+                    .with_system(float_popups.system()),
+            );
+    }
+}
+
+// knock down the nearest target under each shot, unless a solid layer in front takes the bullet,
+// testing against the simulated positions so a round plays out the same at any frame rate
+fn resolve_shots(
+    mut commands: Commands,
+    mut shots: EventReader<Shot>,
+    mut outcomes: EventWriter<ShotOutcome>,
+    targets: Query<(Entity, &Motion, &Target, &Children), Without<KnockedDown>>,
+    faces: Query<(&Transform, &TextureAtlasSprite), With<TargetFace>>,
+    covers: Query<(
+        Entity,
+        &Transform,
+        Option<&Motion>,
+        &TextureAtlasSprite,
+        &Handle<TextureAtlas>,
+        &Cover,
+    )>,
+    masks: Res<AlphaMasks>,
+    texture: Res<Textures>,
+) {
+    for shot in shots.iter() {
+        // whatever the shot hits first, with the sprite the bullet hole sticks to
+        let mut nearest: Option<(Impact, Entity, GlobalTransform)> = None;
+        let is_nearer = |nearest: &Option<(Impact, Entity, GlobalTransform)>, z: f32| {
+            nearest
+                .as_ref()
+                .is_none_or(|(_, _, sprite)| z > sprite.translation.z)
+        };
+
+        for (entity, motion, target, children) in targets.iter() {
+            let face = children
+                .iter()
+                .find_map(|child| faces.get(*child).ok().map(|face| (*child, face)));
+            let (face, (face_local, sprite)) = match face {
+                Some(face) => face,
+                None => continue,
+            };
+            let face_transform =
+                &GlobalTransform::from_translation(motion.current).mul_transform(*face_local);
+            let zone = hit_zone(&target.zones, shot.position - motion.current.truncate());
+            let is_hit = match masks.get(&texture.sprites_objects.atlas, sprite.index) {
+                Some(mask) if target.pixel_perfect => {
+                    mask.contains(face_transform, sprite.flip_x, shot.position)
+                }
+                _ => zone.is_some(),
+            };
+            if is_hit && is_nearer(&nearest, face_transform.translation.z) {
+                let impact = Impact::Target {
+                    entity,
+                    target,
+                    zone,
+                };
+                nearest = Some((impact, face, *face_transform));
+            }
+        }
+
+        for (entity, transform, motion, sprite, atlas, cover) in covers.iter() {
+            let mut placed = *transform;
+            if let Some(motion) = motion {
+                placed.translation = motion.current;
+            }
+            let cover_transform = &GlobalTransform::from(placed);
+            let is_hit = masks
+                .get(atlas, sprite.index)
+                .is_some_and(|mask| mask.contains(cover_transform, sprite.flip_x, shot.position));
+            // solid layers win ties with targets at the same depth
+            let in_front = nearest
+                .as_ref()
+                .is_none_or(|(_, _, sprite)| cover_transform.translation.z >= sprite.translation.z);
+            if is_hit && in_front {
+                nearest = Some((Impact::Cover(cover.decal), entity, *cover_transform));
+            }
+        }
+
+        let outcome = match nearest {
+            Some((
+                Impact::Target {
+                    entity,
+                    target,
+                    zone,
+                },
+                face,
+                face_transform,
+            )) => {
+                commands.entity(entity).insert(KnockedDown {
+                    timer: Timer::from_seconds(target.respawn_time, false),
+                });
+                let decal = texture.sprites_objects.index(DECAL_TARGET);
+                spawn_decal(
+                    &mut commands,
+                    &texture,
+                    decal,
+                    shot.position,
+                    Some((face, face_transform)),
+                );
+                // pixel perfect hits outside every zone still score the base points
+                ShotOutcome::Hit {
+                    position: shot.position,
+                    target: target.name.clone(),
+                    kind: target.kind.clone(),
+                    zone: zone.map_or(EDGE_ZONE, |zone| &zone.name).to_string(),
+                    points: target.points * zone.map_or(1, |zone| zone.multiplier),
+                }
+            }
+            Some((Impact::Cover(Some(decal)), cover, cover_transform)) => {
+                spawn_decal(
+                    &mut commands,
+                    &texture,
+                    decal,
+                    shot.position,
+                    Some((cover, cover_transform)),
+                );
+                ShotOutcome::Miss
+            }
+            Some((Impact::Cover(None), _, _)) => ShotOutcome::Miss,
+            None => {
+                let decal = texture.sprites_objects.index(DECAL_BACKDROP);
+                spawn_decal(&mut commands, &texture, decal, shot.position, None);
+                ShotOutcome::Miss
+            }
+        };
+        outcomes.send(outcome);
+    }
+}
+
+// apply the scoring rules of the kind of target each shot hit
+fn score_shots(
+    mut commands: Commands,
+    mut outcomes: EventReader<ShotOutcome>,
+    mut game: ResMut<Game>,
+    mut stats: ResMut<Stats>,
+    level: Res<Level>,
+    combo: Res<ComboConfig>,
+) {
+    for outcome in outcomes.iter() {
+        let (position, target, kind, zone, points) = match outcome {
+            ShotOutcome::Hit {
+                position,
+                target,
+                kind,
+                zone,
+                points,
+            } => (*position, target, kind, zone, *points),
+            ShotOutcome::Miss => {
+                game.break_combo();
+                game.lose_life();
+                stats.miss();
+                continue;
+            }
+        };
+
+        if let TargetKind::Penalty { points, time } = kind {
+            game.score = game.score.saturating_sub(*points);
+            game.time_left = game.time_left.saturating_sub(*time);
+            game.break_combo();
+            game.lose_life();
+            stats.miss();
+            stats.penalties += 1;
+            continue;
+        }
+
+        let multiplier = game.multiplier(&combo);
+        let points = points * multiplier;
+        game.score += points;
+        game.extend_combo(&combo);
+        stats.hit(target, zone, points, multiplier);
+        spawn_popup(&mut commands, position, points);
+
+        match kind {
+            TargetKind::TimeBonus { seconds } => game.time_left += seconds,
+            TargetKind::AmmoRefill => {
+                game.ammo = game.magazine(&level);
+                game.reload = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+// let the combo run out when the next hit takes too long
+fn decay_combo(clock: Res<SimulationClock>, mut game: ResMut<Game>) {
+    game.decay_combo(clock.delta());
+}
+
+fn spawn_popup(commands: &mut Commands, position: Vec2, points: usize) {
+    let mut display = DigitDisplay::new(DigitFormat::Plus, Padding::None, Align::Center);
+    display.value = points;
+    digits::spawn_digits(commands, position.extend(4.5), display).insert(Popup {
+        timer: Timer::from_seconds(POPUP_TIME, false),
+    });
+}
+
+fn float_popups(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    mut popups: Query<(Entity, &mut Popup, &mut Transform, Option<&Children>)>,
+    mut glyphs: Query<&mut TextureAtlasSprite>,
+) {
+    for (entity, mut popup, mut transform, children) in popups.iter_mut() {
+        popup.timer.tick(clock.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation.y += POPUP_SPEED * clock.delta_seconds();
+        for child in children.iter().flat_map(|children| children.iter()) {
+            if let Ok(mut sprite) = glyphs.get_mut(*child) {
+                sprite.color.set_a(1. - popup.timer.percent());
+            }
+        }
+    }
+}
+
+// leave a bullet hole where a shot landed, riding along with the sprite it hit
+fn spawn_decal(
+    commands: &mut Commands,
+    texture: &Textures,
+    index: u32,
+    position: Vec2,
+    parent: Option<(Entity, GlobalTransform)>,
+) {
+    let bundle = |transform| SpriteSheetBundle {
+        texture_atlas: texture.sprites_objects.atlas.clone(),
+        transform,
+        sprite: TextureAtlasSprite {
+            index,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let decal = Decal {
+        timer: Timer::from_seconds(DECAL_TIME, false),
+    };
+    match parent {
+        Some((parent, parent_transform)) => {
+            let local = parent_transform
+                .compute_matrix()
+                .inverse()
+                .transform_point3(position.extend(parent_transform.translation.z));
+            let transform = Transform {
+                translation: local.truncate().extend(0.001),
+                scale: Vec3::ONE / parent_transform.scale,
+                ..Default::default()
+            };
+            commands.entity(parent).with_children(|parent| {
+                parent.spawn_bundle(bundle(transform)).insert(decal);
+            });
+        }
+        None => {
+            commands
+                .spawn_bundle(bundle(Transform::from_translation(
+                    position.extend(DECAL_BACKDROP_Z),
+                )))
+                .insert(decal);
+        }
+    }
+}
+
+// fade bullet holes out at the end of their lifetime, and drop the oldest ones past the cap
+fn fade_decals(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    config: Res<DecalConfig>,
+    mut decals: Query<(Entity, &mut Decal, &mut TextureAtlasSprite)>,
+) {
+    let mut ages = Vec::new();
+    for (entity, mut decal, mut sprite) in decals.iter_mut() {
+        decal.timer.tick(clock.delta());
+        if decal.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let remaining = decal.timer.duration().as_secs_f32() - decal.timer.elapsed_secs();
+        sprite.color.set_a((remaining / DECAL_FADE_TIME).min(1.));
+        ages.push((decal.timer.elapsed_secs(), entity));
+    }
+    if ages.len() > config.max_decals {
+        ages.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        for (_, entity) in ages.iter().take(ages.len() - config.max_decals) {
+            commands.entity(*entity).despawn_recursive();
+        }
+    }
+}
+
+// cache the alpha masks used for hit testing once the atlas textures are loaded
+fn build_alpha_masks(
+    mut events: EventReader<AssetEvent<Texture>>,
+    texture: Option<Res<Textures>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    textures: Res<Assets<Texture>>,
+    mut masks: ResMut<AlphaMasks>,
+) {
+    let modified: Vec<Handle<Texture>> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => Some(handle.clone()),
+            _ => None,
+        })
+        .collect();
+    let texture = match texture {
+        Some(texture) => texture,
+        None => return,
+    };
+    for handle in [&texture.sprites_stall.atlas, &texture.sprites_objects.atlas].iter() {
+        if let Some(atlas) = atlases.get(*handle) {
+            if !masks.contains(handle) || modified.contains(&atlas.texture) {
+                if let Some(image) = textures.get(&atlas.texture) {
+                    masks.build(handle, atlas, image);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    clock::{Motion, SimulationClock},
+    level::{Level, Scroll},
+    step_set, AssetsReloaded, GameState, Textures, HOT_RELOAD_LABEL, SIMULATION_STAGE,
+};
+use bevy::prelude::*;
+
+// a solid layer of the stall that stops shots, leaving the decal sprite if it has one
+pub struct Cover {
+    pub decal: Option<u32>,
+}
+
+// the scenery of the level, with its scrolling and solid layers
+pub struct StallPlugin;
+
+impl Plugin for StallPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Countdown).with_system(setup_stall.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(respawn_stall.system().after(HOT_RELOAD_LABEL)),
+        )
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::Playing).with_system(animate_stall.system()),
+        );
+    }
+}
+
+fn setup_stall(mut commands: Commands, texture: Res<Textures>, level: Res<Level>) {
+    spawn_stall(&mut commands, &texture, &level);
+}
+
+// put the stall back after the gallery was rebuilt from changed assets
+fn respawn_stall(
+    mut commands: Commands,
+    mut events: EventReader<AssetsReloaded>,
+    texture: Res<Textures>,
+    level: Res<Level>,
+) {
+    if events.iter().count() > 0 {
+        spawn_stall(&mut commands, &texture, &level);
+    }
+}
+
+fn spawn_stall(commands: &mut Commands, texture: &Textures, level: &Level) {
+    for layer in level.layers.iter() {
+        let (count, step) = match &layer.repeat {
+            Some(repeat) => (repeat.count, Vec2::new(repeat.step.0, repeat.step.1)),
+            None => (1, Vec2::ZERO),
+        };
+        for i in 0..count {
+            let position = Vec2::new(layer.position.0, layer.position.1) + step * i as f32;
+            let mut entity = commands.spawn_bundle(SpriteSheetBundle {
+                texture_atlas: texture.sprites_stall.atlas.clone(),
+                transform: Transform {
+                    translation: position.extend(layer.z),
+                    scale: Vec3::splat(layer.scale),
+                    ..Default::default()
+                },
+                sprite: TextureAtlasSprite {
+                    index: texture.sprites_stall.index(&layer.sprite),
+                    flip_x: layer.flip_x,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            if let Some(scroll) = &layer.scroll {
+                entity
+                    .insert(scroll.clone())
+                    .insert(Motion::new(position.extend(layer.z)));
+            }
+            if layer.solid {
+                entity.insert(Cover {
+                    decal: layer
+                        .decal
+                        .as_ref()
+                        .map(|decal| texture.sprites_objects.index(decal)),
+                });
+            }
+        }
+    }
+}
+
+fn animate_stall(clock: Res<SimulationClock>, mut query: Query<(&mut Motion, &Scroll)>) {
+    for (mut motion, scroll) in query.iter_mut() {
+        motion.previous = motion.current;
+        motion.current.x += scroll.speed * clock.delta_seconds();
+
+        if scroll.speed > 0. && motion.current.x > scroll.max {
+            motion.current.x = scroll.min;
+            motion.previous = motion.current;
+        } else if scroll.speed < 0. && motion.current.x < scroll.min {
+            motion.current.x = scroll.max;
+            motion.previous = motion.current;
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    clock::{Motion, SimulationClock},
+    hit::Zone,
+    level::{Level, TargetKind, TargetSpawn},
+    movement::Movement,
+    rng::GameRng,
+    step_set,
+    waves::Difficulty,
+    AssetsReloaded, GameState, Textures, HOT_RELOAD_LABEL, SEED_LABEL, SIMULATION_STAGE,
+};
+use bevy::prelude::*;
+
+const FALL_TIME: f32 = 0.4;
+const FALL_DISTANCE: f32 = 60.;
+const TELEPORT_DISTANCE: f32 = 200.;
+const GOLD: Color = Color::rgb(1., 0.8, 0.1);
+// wave targets of the same spawn are drawn a little apart so they don't flicker where they overlap,
+// cycling through few enough steps to stay between the level's layers
+const WAVE_DEPTH_STEP: f32 = 0.0001;
+const WAVE_DEPTHS: usize = 10;
+
+pub struct Target {
+    pub name: String,
+    pub kind: TargetKind,
+    pub points: usize,
+    pub zones: Vec<Zone>,
+    pub pixel_perfect: bool,
+    pub respawn_time: f32,
+}
+pub struct TargetFace;
+pub struct TargetStick {
+    pub whole: u32,
+    pub broken: u32,
+}
+pub struct KnockedDown {
+    pub timer: Timer,
+}
+// a target brought in by a wave, leaving when its time is up or for good once shot
+pub struct WaveTarget {
+    pub timer: Timer,
+    // added to the z of its spawn
+    pub depth: f32,
+}
+// brings in the level's waves while the round is played
+pub struct WaveDirector {
+    pub wave: usize,
+    // wave targets brought in so far
+    pub arrived: usize,
+    pub next_wave: Timer,
+    pub pending: Vec<PendingTarget>,
+}
+// a target of the current wave waiting for its turn to come in
+pub struct PendingTarget {
+    pub delay: Timer,
+    pub name: String,
+    pub speed: f32,
+    pub start: f32,
+}
+
+// the level's targets and waves, moving through their patterns and going down when hit
+pub struct TargetsPlugin;
+
+impl Plugin for TargetsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Countdown)
+                .with_system(setup_targets.system())
+                .with_system(start_waves.system().after(SEED_LABEL)),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(respawn_targets.system().after(HOT_RELOAD_LABEL)),
+        )
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::Playing)
+                .with_system(animate_targets.system())
+                .with_system(knock_down_targets.system())
+                .with_system(direct_waves.system())
+                .with_system(expire_wave_targets.system()),
+        )
+        .add_system_set_to_stage(
+            SIMULATION_STAGE,
+            step_set(GameState::TimeUp).with_system(knock_down_targets.system()),
+        );
+    }
+}
+
+fn setup_targets(mut commands: Commands, texture: Res<Textures>, level: Res<Level>) {
+    spawn_targets(&mut commands, &texture, &level);
+}
+
+// put the targets back after the gallery was rebuilt from changed assets, the wave targets on screen
+// where they were as long as the level still has them
+fn respawn_targets(
+    mut commands: Commands,
+    mut events: EventReader<AssetsReloaded>,
+    texture: Res<Textures>,
+    level: Res<Level>,
+    wave_targets: Query<(&Target, &Movement, &WaveTarget), Without<KnockedDown>>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    spawn_targets(&mut commands, &texture, &level);
+    // the old ones are only despawned once the commands are applied
+    for (target, movement, wave_target) in wave_targets.iter() {
+        if let Some(spawn) = level.target(&target.name) {
+            let speed = movement.speed;
+            let start = movement.elapsed;
+            let depth = wave_target.depth;
+            let entity = spawn_target(&mut commands, &texture, &level, spawn, speed, start, depth);
+            commands.entity(entity).insert(WaveTarget {
+                timer: wave_target.timer.clone(),
+                depth,
+            });
+        }
+    }
+}
+
+fn spawn_targets(commands: &mut Commands, texture: &Textures, level: &Level) {
+    for target in level.targets.iter() {
+        if !level.waves.iter().any(|waves| waves.contains(&target.name)) {
+            spawn_target(commands, texture, level, target, 1., 0., 0.);
+        }
+    }
+}
+
+// spawn a target with its face and optional stick as children, so they move and die together,
+// starting some seconds into its movement and drawn a little in front of its spawn's z
+pub fn spawn_target(
+    commands: &mut Commands,
+    texture: &Textures,
+    level: &Level,
+    spawn: &TargetSpawn,
+    speed: f32,
+    start: f32,
+    depth: f32,
+) -> Entity {
+    let movement = Movement {
+        pattern: spawn.movement.clone(),
+        pause: spawn.pause.clone(),
+        origin: Vec2::new(spawn.position.0, spawn.position.1),
+        track: level.track,
+        elapsed: start,
+        speed,
+    };
+    let position = movement.position().extend(spawn.z + depth);
+    commands
+        .spawn_bundle((
+            Transform::from_translation(position),
+            GlobalTransform::identity(),
+        ))
+        .insert(Motion::new(position))
+        .insert(movement)
+        .insert(Target {
+            name: spawn.name.clone(),
+            kind: spawn.kind.clone(),
+            points: spawn.points,
+            zones: level.hit_area(&spawn.hit_area).to_vec(),
+            pixel_perfect: spawn.pixel_perfect,
+            respawn_time: spawn.respawn_time,
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: texture.sprites_objects.atlas.clone(),
+                    sprite: TextureAtlasSprite {
+                        index: texture.sprites_objects.index(&spawn.face),
+                        color: match spawn.kind {
+                            TargetKind::Golden => GOLD,
+                            _ => Color::WHITE,
+                        },
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(TargetFace);
+            if let Some(stick) = &spawn.stick {
+                let whole = texture.sprites_objects.index(&stick.sprite);
+                parent
+                    .spawn_bundle(SpriteSheetBundle {
+                        texture_atlas: texture.sprites_objects.atlas.clone(),
+                        transform: Transform::from_xyz(stick.offset.0, stick.offset.1, -0.01),
+                        sprite: TextureAtlasSprite {
+                            index: whole,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(TargetStick {
+                        whole,
+                        broken: texture.sprites_objects.index(&stick.broken_sprite()),
+                    });
+            }
+        })
+        .id()
+}
+
+fn start_waves(
+    mut commands: Commands,
+    level: Res<Level>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+) {
+    let mut director = WaveDirector {
+        wave: 0,
+        arrived: 0,
+        next_wave: Timer::from_seconds(0., true),
+        pending: Vec::new(),
+    };
+    if let Some(waves) = &level.waves {
+        director.next_wave = Timer::from_seconds(waves.interval, true);
+        schedule_wave(&mut director, &level, *difficulty, &mut rng);
+    }
+    commands.insert_resource(director);
+}
+
+// queue the targets of the director's next wave, each at its time in the wave
+fn schedule_wave(
+    director: &mut WaveDirector,
+    level: &Level,
+    difficulty: Difficulty,
+    rng: &mut GameRng,
+) {
+    let waves = match &level.waves {
+        Some(waves) => waves,
+        None => return,
+    };
+    let plan = waves.plan(director.wave, difficulty, rng);
+    for target in plan.targets {
+        director.pending.push(PendingTarget {
+            delay: Timer::from_seconds(target.delay, false),
+            name: target.name,
+            speed: plan.speed,
+            start: target.start,
+        });
+    }
+    director.wave += 1;
+}
+
+fn direct_waves(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    texture: Res<Textures>,
+    level: Res<Level>,
+    difficulty: Res<Difficulty>,
+    mut director: ResMut<WaveDirector>,
+    mut rng: ResMut<GameRng>,
+) {
+    let waves = match &level.waves {
+        Some(waves) => waves,
+        None => return,
+    };
+    if director.next_wave.tick(clock.delta()).just_finished() {
+        schedule_wave(&mut director, &level, *difficulty, &mut rng);
+    }
+
+    let mut arrived = Vec::new();
+    director.pending.retain_mut(|pending| {
+        let ready = pending.delay.tick(clock.delta()).finished();
+        if ready {
+            arrived.push((pending.name.clone(), pending.speed, pending.start));
+        }
+        !ready
+    });
+    for (name, speed, start) in arrived {
+        match level.target(&name) {
+            Some(spawn) => {
+                let depth = (director.arrived % WAVE_DEPTHS) as f32 * WAVE_DEPTH_STEP;
+                director.arrived += 1;
+                let entity =
+                    spawn_target(&mut commands, &texture, &level, spawn, speed, start, depth);
+                commands.entity(entity).insert(WaveTarget {
+                    timer: Timer::from_seconds(waves.lifetime, false),
+                    depth,
+                });
+            }
+            None => warn!("wave target {} is not in the level", name),
+        }
+    }
+}
+
+// send wave targets away once their time is up, unless they're already going down
+fn expire_wave_targets(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    mut targets: Query<(Entity, &mut WaveTarget), Without<KnockedDown>>,
+) {
+    for (entity, mut wave_target) in targets.iter_mut() {
+        if wave_target.timer.tick(clock.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn animate_targets(clock: Res<SimulationClock>, mut query: Query<(&mut Motion, &mut Movement)>) {
+    for (mut motion, mut movement) in query.iter_mut() {
+        movement.elapsed += clock.delta_seconds() * movement.speed;
+        motion.previous = motion.current;
+        motion.current = movement.position().extend(motion.current.z);
+
+        // don't interpolate across the screen when a target wraps around
+        if motion.previous.distance(motion.current) > TELEPORT_DISTANCE {
+            motion.previous = motion.current;
+        }
+    }
+}
+
+// flip a hit target's face down, break its stick and stand it back up once its respawn delay is over
+fn knock_down_targets(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    mut targets: Query<(Entity, &mut KnockedDown, &Children, Option<&WaveTarget>)>,
+    mut faces: Query<(&mut Transform, &mut Visible, Option<&Children>), With<TargetFace>>,
+    mut sticks: Query<(&mut TextureAtlasSprite, &TargetStick)>,
+) {
+    for (entity, mut knocked_down, children, wave_target) in targets.iter_mut() {
+        knocked_down.timer.tick(clock.delta());
+        let respawned = knocked_down.timer.finished();
+        if respawned && wave_target.is_some() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let fall = (knocked_down.timer.elapsed_secs() / FALL_TIME).min(1.);
+
+        for child in children.iter() {
+            if let Ok((mut transform, mut visible, decals)) = faces.get_mut(*child) {
+                if respawned {
+                    transform.translation.y = 0.;
+                    transform.scale.x = 1.;
+                    visible.is_visible = true;
+                } else {
+                    transform.translation.y = -fall * FALL_DISTANCE;
+                    transform.scale.x = (fall * std::f32::consts::PI).cos();
+                    visible.is_visible = fall < 1.;
+                }
+                // bullet holes go down with the face
+                if !visible.is_visible {
+                    for decal in decals.iter().flat_map(|decals| decals.iter()) {
+                        commands.entity(*decal).despawn_recursive();
+                    }
+                }
+            }
+            if let Ok((mut sprite, stick)) = sticks.get_mut(*child) {
+                sprite.index = if respawned { stick.whole } else { stick.broken };
+            }
+        }
+
+        if respawned {
+            commands.entity(entity).remove::<KnockedDown>();
+        }
+    }
+}
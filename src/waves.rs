@@ -0,0 +1,301 @@
+use rand::Rng;
+use serde::Deserialize;
+
+// how hard the waves of a level get, picked from the main menu
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    // names the difficulty in high score tables and replay files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Difficulty> {
+        match key {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+// targets brought in a group at a time over the round instead of all at once
+#[derive(Clone, Debug, Deserialize)]
+pub struct Waves {
+    // seconds between the starts of two waves
+    pub interval: f32,
+    // seconds between two targets of the same wave
+    pub spacing: f32,
+    // seconds a target stays in the gallery, it's gone for good once shot
+    pub lifetime: f32,
+    // seconds a target may come in before or after its turn, picked at random
+    #[serde(default)]
+    pub jitter: f32,
+    // pick each target at random from those in the waves so far instead of taking turns
+    #[serde(default)]
+    pub shuffle: bool,
+    // seconds into its movement a target may start at, picked at random
+    #[serde(default)]
+    pub scatter: f32,
+    pub groups: Vec<WaveGroup>,
+    pub curves: Curves,
+}
+
+// targets that join the waves from a given wave on
+#[derive(Clone, Debug, Deserialize)]
+pub struct WaveGroup {
+    #[serde(default)]
+    pub from_wave: usize,
+    // names of targets of the level
+    pub targets: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Curves {
+    pub easy: Curve,
+    pub normal: Curve,
+    pub hard: Curve,
+}
+
+// how the waves escalate, each value grows by its step with every wave up to its max
+#[derive(Clone, Debug, Deserialize)]
+pub struct Curve {
+    pub count: f32,
+    pub count_step: f32,
+    pub max_count: usize,
+    // scales how fast the targets go through their movement patterns
+    pub speed: f32,
+    pub speed_step: f32,
+    pub max_speed: f32,
+    // waves by which groups join earlier than their from_wave
+    #[serde(default)]
+    pub head_start: usize,
+}
+
+// the targets of one wave, in spawn order, and how fast they move
+#[derive(Debug, PartialEq)]
+pub struct WavePlan {
+    pub targets: Vec<PlannedTarget>,
+    pub speed: f32,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PlannedTarget {
+    pub name: String,
+    // seconds after the start of the wave
+    pub delay: f32,
+    // seconds into its movement the target starts at
+    pub start: f32,
+}
+
+impl Waves {
+    // times that would stall the waves or panic when drawing the random parts
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.interval <= 0. {
+            anyhow::bail!("waves need some time between them");
+        }
+        if self.spacing < 0. || self.lifetime < 0. {
+            anyhow::bail!("spacing and lifetime can't be negative");
+        }
+        Ok(())
+    }
+
+    pub fn curve(&self, difficulty: Difficulty) -> &Curve {
+        match difficulty {
+            Difficulty::Easy => &self.curves.easy,
+            Difficulty::Normal => &self.curves.normal,
+            Difficulty::Hard => &self.curves.hard,
+        }
+    }
+
+    // wave counts from 0, the random parts are drawn target by target in spawn order
+    pub fn plan(&self, wave: usize, difficulty: Difficulty, rng: &mut impl Rng) -> WavePlan {
+        let curve = self.curve(difficulty);
+        let names: Vec<&String> = self
+            .groups
+            .iter()
+            .filter(|group| group.from_wave <= wave + curve.head_start)
+            .flat_map(|group| group.targets.iter())
+            .collect();
+        let count = if names.is_empty() {
+            0
+        } else {
+            ((curve.count + curve.count_step * wave as f32) as usize).min(curve.max_count)
+        };
+        // start each wave further along the list so every target gets its turn
+        let mut turns = names.iter().cycle().skip(wave);
+        let targets = (0..count)
+            .map(|slot| {
+                let name = if self.shuffle {
+                    names[rng.gen_range(0..names.len())]
+                } else {
+                    turns.next().unwrap()
+                };
+                let jitter = if self.jitter > 0. {
+                    rng.gen_range(-self.jitter..self.jitter)
+                } else {
+                    0.
+                };
+                let start = if self.scatter > 0. {
+                    rng.gen_range(0. ..self.scatter)
+                } else {
+                    0.
+                };
+                PlannedTarget {
+                    name: name.to_string(),
+                    delay: (slot as f32 * self.spacing + jitter).max(0.),
+                    start,
+                }
+            })
+            .collect();
+        WavePlan {
+            targets,
+            speed: (curve.speed + curve.speed_step * wave as f32).min(curve.max_speed),
+        }
+    }
+
+    // whether a target is only brought in by the waves
+    pub fn contains(&self, target: &str) -> bool {
+        self.groups
+            .iter()
+            .any(|group| group.targets.iter().any(|name| name == target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn curve(count: f32, speed: f32, head_start: usize) -> Curve {
+        Curve {
+            count,
+            count_step: 1.,
+            max_count: 4,
+            speed,
+            speed_step: 0.5,
+            max_speed: 2.,
+            head_start,
+        }
+    }
+
+    fn waves() -> Waves {
+        Waves {
+            interval: 10.,
+            spacing: 2.,
+            lifetime: 8.,
+            jitter: 0.,
+            shuffle: false,
+            scatter: 0.,
+            groups: vec![
+                WaveGroup {
+                    from_wave: 0,
+                    targets: vec!["a".to_string(), "b".to_string()],
+                },
+                WaveGroup {
+                    from_wave: 2,
+                    targets: vec!["c".to_string()],
+                },
+            ],
+            curves: Curves {
+                easy: curve(1., 0.5, 0),
+                normal: curve(2., 1., 0),
+                hard: curve(2., 1., 2),
+            },
+        }
+    }
+
+    fn names(plan: &WavePlan) -> Vec<&str> {
+        plan.targets
+            .iter()
+            .map(|target| target.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn waves_need_time_between_them() {
+        assert!(waves().check().is_ok());
+        let mut stalled = waves();
+        stalled.interval = 0.;
+        assert!(stalled.check().is_err());
+        let mut backwards = waves();
+        backwards.lifetime = -1.;
+        assert!(backwards.check().is_err());
+    }
+
+    #[test]
+    fn waves_grow_and_take_turns() {
+        let waves = waves();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let first = waves.plan(0, Difficulty::Normal, &mut rng);
+        assert_eq!(names(&first), ["a", "b"]);
+        assert_eq!(first.speed, 1.);
+        let delays: Vec<f32> = first.targets.iter().map(|target| target.delay).collect();
+        assert_eq!(delays, [0., 2.]);
+
+        let second = waves.plan(1, Difficulty::Normal, &mut rng);
+        assert_eq!(names(&second), ["b", "a", "b"]);
+        assert_eq!(second.speed, 1.5);
+    }
+
+    #[test]
+    fn counts_and_speeds_stop_at_their_max() {
+        let plan = waves().plan(5, Difficulty::Normal, &mut ChaCha8Rng::seed_from_u64(0));
+        assert_eq!(plan.targets.len(), 4);
+        assert_eq!(plan.speed, 2.);
+    }
+
+    #[test]
+    fn groups_join_from_their_wave_or_earlier_with_a_head_start() {
+        let waves = waves();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let normal = waves.plan(0, Difficulty::Normal, &mut rng);
+        assert!(!names(&normal).contains(&"c"));
+        let later = waves.plan(2, Difficulty::Normal, &mut rng);
+        assert!(names(&later).contains(&"c"));
+        let hard = waves.plan(1, Difficulty::Hard, &mut rng);
+        assert_eq!(names(&hard), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn random_parts_follow_the_seed() {
+        let mut waves = waves();
+        waves.jitter = 1.;
+        waves.shuffle = true;
+        waves.scatter = 3.;
+        let plan = |seed| waves.plan(3, Difficulty::Normal, &mut ChaCha8Rng::seed_from_u64(seed));
+        assert_eq!(plan(7), plan(7));
+        for target in plan(7).targets {
+            assert!(target.delay >= 0.);
+            assert!((0. ..3.).contains(&target.start));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::math::Vec2;
+use rust_2d_shooting_gallery::{
+    clock::STEPS_PER_SECOND, headless::Simulation, mode::GameMode, replay::Replay,
+    waves::Difficulty, GameState,
+};
+
+// above the targets' rows on the back wall of the stall
+const BACKDROP: (f32, f32) = (0., 330.);
+
+// a seeded round played by the simulation's bot
+fn play(mode: GameMode, seed: u64) -> Simulation {
+    let mut simulation = Simulation::new(mode, Some(seed)).unwrap();
+    assert_eq!(simulation.state(), GameState::MainMenu);
+    simulation.play_round();
+    simulation
+}
+
+// a seeded round past its intro
+fn start(mode: GameMode, seed: u64) -> Simulation {
+    let mut simulation = Simulation::new(mode, Some(seed)).unwrap();
+    simulation.start_round();
+    assert_eq!(simulation.state(), GameState::Playing);
+    simulation
+}
+
+// one shot at the empty backdrop
+fn miss(simulation: &mut Simulation) {
+    let (shots, hits) = (simulation.stats().shots, simulation.stats().hits);
+    simulation.move_cursor(Vec2::from(BACKDROP));
+    simulation.click();
+    simulation.step();
+    assert_eq!(simulation.stats().shots, shots + 1);
+    assert_eq!(simulation.stats().hits, hits);
+}
+
+#[test]
+fn seeded_round_plays_to_the_results() {
+    let simulation = play(GameMode::FixedAmmo, 3);
+    assert_eq!(simulation.state(), GameState::GameOver);
+    // the round ends on the last bullet
+    assert_eq!(simulation.game().shots_left, Some(0));
+    assert!(simulation.game().score > 0);
+    assert_eq!(simulation.seed(), 3);
+}
+
+#[test]
+fn same_seed_and_shots_score_the_same() {
+    let first = play(GameMode::SuddenDeath, 11);
+    let second = play(GameMode::SuddenDeath, 11);
+    assert_eq!(first.state(), GameState::GameOver);
+    assert_eq!(first.game().score, second.game().score);
+    assert_eq!(first.stats().hits, second.stats().hits);
+}
+
+#[test]
+fn recorded_round_plays_back_to_the_same_score() {
+    let played = play(GameMode::Classic, 5);
+    assert_eq!(played.state(), GameState::GameOver);
+    let replay = played.replay();
+    assert_eq!(replay.score, played.game().score);
+    assert_eq!(replay.difficulty(), Difficulty::Normal);
+
+    // through a file, the way rounds are played back from the command line
+    let path = std::env::temp_dir().join(format!("gallery-replay-{}.ron", std::process::id()));
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut simulation = Simulation::new(loaded.mode(), Some(loaded.seed)).unwrap();
+    simulation.play_back(loaded);
+    assert_eq!(simulation.state(), GameState::GameOver);
+    assert_eq!(simulation.game().score, played.game().score);
+    assert_eq!(simulation.stats().hits, played.stats().hits);
+}
+
+#[test]
+fn sudden_death_ends_on_the_first_miss() {
+    let mut simulation = start(GameMode::SuddenDeath, 13);
+    miss(&mut simulation);
+    assert_eq!(simulation.game().lives, Some(0));
+    simulation.step_until(GameState::GameOver);
+    assert_eq!(simulation.state(), GameState::GameOver);
+}
+
+#[test]
+fn endless_rounds_lose_a_life_per_miss() {
+    let mut simulation = start(GameMode::Endless, 13);
+    for lives in (0..3).rev() {
+        assert_eq!(simulation.state(), GameState::Playing);
+        miss(&mut simulation);
+        assert_eq!(simulation.game().lives, Some(lives));
+    }
+    simulation.step_until(GameState::GameOver);
+    assert_eq!(simulation.state(), GameState::GameOver);
+}
+
+#[test]
+fn zen_rounds_never_end_on_their_own() {
+    let mut simulation = start(GameMode::Zen, 13);
+    for _ in 0..3 {
+        miss(&mut simulation);
+    }
+    // well past the length of a timed round
+    for _ in 0..120 * STEPS_PER_SECOND {
+        simulation.step();
+    }
+    assert_eq!(simulation.state(), GameState::Playing);
+    assert_eq!(simulation.game().lives, None);
+}
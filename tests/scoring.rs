@@ -0,0 +1,64 @@
+use rust_2d_shooting_gallery::{headless::Simulation, mode::GameMode, GameState};
+
+// a seeded classic round past its intro
+fn start(seed: u64) -> Simulation {
+    let mut simulation = Simulation::new(GameMode::Classic, Some(seed)).unwrap();
+    simulation.start_round();
+    assert_eq!(simulation.state(), GameState::Playing);
+    simulation
+}
+
+// wait for a target to come out and shoot it once
+fn shoot(simulation: &mut Simulation, name: &str) {
+    let position = simulation
+        .wait_for(name)
+        .unwrap_or_else(|| panic!("{} never came out", name));
+    simulation.move_cursor(position);
+    simulation.click();
+    simulation.step();
+}
+
+#[test]
+fn penalty_targets_take_points_and_time() {
+    let mut simulation = start(2);
+    shoot(&mut simulation, "Time Target");
+    assert_eq!(simulation.stats().hits, 1);
+
+    let position = simulation.wait_for("Wrong Way Duck").unwrap();
+    let (score, time_left) = (simulation.game().score, simulation.game().time_left);
+    simulation.move_cursor(position);
+    simulation.click();
+    simulation.step();
+    assert_eq!(simulation.stats().penalties, 1);
+    assert_eq!(simulation.game().score, score.saturating_sub(50));
+    // the clock may tick a second away in the same step
+    assert!((time_left - 6..=time_left - 5).contains(&simulation.game().time_left));
+    assert_eq!(simulation.game().combo, 0);
+}
+
+#[test]
+fn time_bonus_targets_add_seconds() {
+    let mut simulation = start(4);
+    let position = simulation.wait_for("Time Target").unwrap();
+    let time_left = simulation.game().time_left;
+    simulation.move_cursor(position);
+    simulation.click();
+    simulation.step();
+    assert_eq!(simulation.stats().hits, 1);
+    assert!((time_left + 4..=time_left + 5).contains(&simulation.game().time_left));
+}
+
+#[test]
+fn ammo_refills_cut_a_reload_short() {
+    let mut simulation = start(6);
+    let position = simulation.wait_for("Ammo Duck").unwrap();
+    let magazine = simulation.game().ammo;
+    simulation.move_cursor(position);
+    // the reload starts behind the shot that hits the duck
+    simulation.click();
+    simulation.right_click();
+    simulation.step();
+    assert_eq!(simulation.stats().hits, 1);
+    assert!(simulation.game().reload.is_none());
+    assert_eq!(simulation.game().ammo, magazine);
+}